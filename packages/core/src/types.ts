@@ -38,6 +38,14 @@ export interface Suggestion {
   description: string
   /** The replacement text */
   replacement: string
+  /** Text to replace; when absent, the finding's span */
+  span?: SourceSpan
+  /**
+   * Whether the suggestion can be applied without review
+   * (`machineApplicable`) or may change the query's results
+   * (`maybeIncorrect`). Absent when the rule doesn't say.
+   */
+  applicability?: 'machineApplicable' | 'maybeIncorrect'
 }
 
 /**
@@ -56,6 +64,8 @@ export interface Finding {
   help?: string
   /** Suggested fixes */
   suggestions?: Suggestion[]
+  /** Whether a suggestion can be applied automatically, e.g. by `--fix` */
+  fixable?: boolean
 }
 
 /**
@@ -53,7 +53,8 @@ export async function initLinter(): Promise<boolean> {
 /**
  * Lint a GROQ query
  *
- * Uses WASM for pure GROQ rules (if available) and TypeScript for schema-aware rules.
+ * Uses WASM for pure GROQ and schema-aware rules (if available), and TypeScript
+ * for the rest.
  * Call `initLinter()` first to enable WASM support.
  *
  * @param query - The GROQ query string to lint
@@ -77,7 +78,7 @@ export function lint(query: string, options?: LintOptions): LintResult {
   const tsRules: Rule[] = []
 
   for (const rule of enabledRules) {
-    if (!forceTs && isWasmAvailable() && isWasmRule(rule.id)) {
+    if (!forceTs && isWasmAvailable() && isWasmRule(rule.id, schema)) {
       wasmRuleIds.add(rule.id)
     } else {
       tsRules.push(rule)
@@ -88,7 +89,7 @@ export function lint(query: string, options?: LintOptions): LintResult {
   const wasmFindings: Finding[] = []
   if (wasmRuleIds.size > 0 && isWasmAvailable()) {
     try {
-      const wf = lintWithWasm(query, wasmRuleIds)
+      const wf = lintWithWasm(query, wasmRuleIds, schema)
       wasmFindings.push(...wf)
     } catch {
       // WASM failed - fall back to TS rules
@@ -9,6 +9,7 @@ import { readSchema } from '@sanity/codegen'
 import type { SchemaType } from 'groq-js'
 import { existsSync } from 'node:fs'
 import { resolve } from 'node:path'
+import { getWasmSchema } from './wasm-linter'
 
 /**
  * Load a schema from a JSON file path.
@@ -76,6 +77,11 @@ export async function tryLoadSchema(root: string = process.cwd()): Promise<Schem
  * @returns Array of document type names
  */
 export function getDocumentTypes(schema: SchemaType): string[] {
+  const wasmSchema = getWasmSchema(schema)
+  if (wasmSchema) {
+    return wasmSchema.documentTypes()
+  }
+
   return schema
     .filter((item): item is Extract<typeof item, { type: 'document' }> => item.type === 'document')
     .map((doc) => doc.name)
@@ -106,6 +112,11 @@ export function getDocumentByType(
  * @returns Array of field names, or empty array if type not found
  */
 export function getFieldsForType(schema: SchemaType, typeName: string): string[] {
+  const wasmSchema = getWasmSchema(schema)
+  if (wasmSchema) {
+    return wasmSchema.fieldsForType(typeName)
+  }
+
   const doc = getDocumentByType(schema, typeName)
   if (!doc) {
     return []
@@ -5,7 +5,7 @@
  * Falls back gracefully if WASM is not available.
  */
 
-import type { Finding } from '@sanity-labs/lint-core'
+import type { Finding, SchemaType } from '@sanity-labs/lint-core'
 
 // WASM module state
 let wasmAvailable = false
@@ -14,32 +14,37 @@ let wasmInitPromise: Promise<boolean> | null = null
 
 // Import types for the WASM module
 type WasmModule = typeof import('@sanity-labs/groq-wasm')
+type WasmSchema = InstanceType<WasmModule['Schema']>
 let wasmModule: WasmModule | null = null
 
+// The indexed WASM schema, and the schema object it was built from. A
+// single handle is kept and updated when the schema changes (e.g. when the
+// LSP reloads it), so replaced schemas don't pile up on the WASM heap
+let wasmSchema: WasmSchema | null = null
+let wasmSchemaSource: SchemaType | null = null
+
 /**
  * Rules available in the WASM linter (from Rust groq-lint)
+ *
+ * Filled from the WASM module's `rules()` once it is initialized, with
+ * every rule that needs nothing but the query
+ */
+export const WASM_RULES = new Set<string>()
+
+/**
+ * Schema-aware rules available in the WASM linter, run when a schema is
+ * given
  */
-export const WASM_RULES = new Set([
-  'join-in-filter',
-  'join-to-get-id',
-  'computed-value-in-filter',
-  'match-on-id',
-  'order-on-expr',
-  'deep-pagination',
-  'large-pages',
-  'non-literal-comparison',
-  'repeated-dereference',
-  'count-in-correlated-subquery',
-  'very-large-query',
-  'extremely-large-query',
-  'many-joins',
-])
+export const WASM_SCHEMA_RULES = new Set<string>()
 
 /**
  * Check if a rule is available in WASM
+ *
+ * @param ruleId - The rule ID
+ * @param schema - The schema being linted against, if any
  */
-export function isWasmRule(ruleId: string): boolean {
-  return WASM_RULES.has(ruleId)
+export function isWasmRule(ruleId: string, schema?: SchemaType): boolean {
+  return WASM_RULES.has(ruleId) || (schema !== undefined && WASM_SCHEMA_RULES.has(ruleId))
 }
 
 /**
@@ -65,6 +70,12 @@ async function doInit(): Promise<boolean> {
     // Dynamic import to avoid bundling issues
     wasmModule = await import('@sanity-labs/groq-wasm')
     await wasmModule.initWasm()
+    for (const rule of wasmModule.rules()) {
+      if (rule.requiresParams || rule.requiresStats) {
+        continue
+      }
+      ;(rule.requiresSchema ? WASM_SCHEMA_RULES : WASM_RULES).add(rule.kebabId)
+    }
     wasmAvailable = true
     wasmInitialized = true
     return true
@@ -83,28 +94,53 @@ export function isWasmAvailable(): boolean {
   return wasmAvailable
 }
 
+/**
+ * The indexed WASM schema for a schema object, or null without WASM
+ *
+ * The schema is serialized and indexed once per schema object. A different
+ * object is loaded into the same handle with `update()`.
+ */
+export function getWasmSchema(schema: SchemaType): WasmSchema | null {
+  if (!wasmAvailable || !wasmModule) {
+    return null
+  }
+
+  if (!wasmSchema) {
+    wasmSchema = new wasmModule.Schema(schema)
+  } else if (wasmSchemaSource !== schema) {
+    wasmSchema.update(schema)
+  }
+  wasmSchemaSource = schema
+  return wasmSchema
+}
+
 /**
  * Lint a query using WASM
  *
  * @param query - The GROQ query to lint
  * @param enabledRules - Optional set of rule IDs to enable (all if not provided)
+ * @param schema - Schema to run the schema-aware rules against
  * @returns Array of findings from WASM linter
  * @throws If WASM is not initialized
  */
-export function lintWithWasm(query: string, enabledRules?: Set<string>): Finding[] {
+export function lintWithWasm(
+  query: string,
+  enabledRules?: Set<string>,
+  schema?: SchemaType
+): Finding[] {
   if (!wasmAvailable || !wasmModule) {
     throw new Error('WASM linter not initialized. Call initWasmLinter() first.')
   }
 
-  // Get findings from WASM
-  const wasmFindings = wasmModule.lint(query)
-
-  // Filter by enabled rules if provided
-  if (enabledRules) {
-    return wasmFindings.filter((f) => enabledRules.has(f.ruleId))
-  }
+  // Rules that aren't enabled are switched off in Rust rather than
+  // filtered out afterwards, so they never run
+  const wasmRules = schema ? [...WASM_RULES, ...WASM_SCHEMA_RULES] : [...WASM_RULES]
+  const rules = enabledRules
+    ? Object.fromEntries(wasmRules.map((ruleId) => [ruleId, enabledRules.has(ruleId)]))
+    : undefined
 
-  return wasmFindings
+  const indexed = schema && getWasmSchema(schema)
+  return indexed ? indexed.lint(query, { rules }) : wasmModule.lint(query, { rules })
 }
 
 /**
@@ -114,11 +150,12 @@ export function lintWithWasm(query: string, enabledRules?: Set<string>): Finding
  */
 export async function lintWithWasmAsync(
   query: string,
-  enabledRules?: Set<string>
+  enabledRules?: Set<string>,
+  schema?: SchemaType
 ): Promise<Finding[]> {
   const available = await initWasmLinter()
   if (!available) {
     return [] // Return empty - caller should use TS fallback
   }
-  return lintWithWasm(query, enabledRules)
+  return lintWithWasm(query, enabledRules, schema)
 }
@@ -1,10 +1,17 @@
 /**
  * Formatting capability for the GROQ Language Server
  *
- * Uses prettier-plugin-groq for GROQ formatting
+ * Uses prettier-plugin-groq for GROQ formatting, and groq-wasm directly for
+ * minimal document edits and for formatting a selection
  */
 
+import {
+  formatEdits,
+  formatRange,
+  isInitialized as isWasmInitialized,
+} from '@sanity-labs/groq-wasm'
 import type { TextEdit, Range } from 'vscode-languageserver'
+import { TextDocument } from 'vscode-languageserver-textdocument'
 import type { GroqQuery } from '../types.js'
 
 /**
@@ -65,12 +72,21 @@ export async function formatQuery(
 
 /**
  * Format all queries in a document
+ *
+ * With the WASM formatter, each query gets the minimal edits that format
+ * it, so the cursor and undo history outside the changed text are kept.
+ * Without it, each query is replaced by prettier's output.
  */
 export async function formatDocument(
   queries: GroqQuery[],
-  _documentContent: string,
+  documentContent: string,
   options: FormattingOptions = {}
 ): Promise<TextEdit[]> {
+  if (isWasmInitialized()) {
+    const document = TextDocument.create('', 'groq', 0, documentContent)
+    return queries.flatMap((query) => formatQueryEdits(query, document, options))
+  }
+
   const edits: TextEdit[] = []
 
   // Process queries in reverse order so edits don't affect subsequent positions
@@ -84,6 +100,88 @@ export async function formatDocument(
   return edits
 }
 
+/**
+ * Minimal edits that format a query, in document positions
+ */
+function formatQueryEdits(
+  query: GroqQuery,
+  document: TextDocument,
+  options: FormattingOptions
+): TextEdit[] {
+  try {
+    const { edits } = formatEdits(
+      query.query,
+      options.printWidth ? { width: options.printWidth } : undefined
+    )
+
+    // Both TextDocument and groq-wasm count UTF-16 code units
+    return edits.map((edit) => ({
+      range: {
+        start: document.positionAt(query.start + edit.start),
+        end: document.positionAt(query.start + edit.end),
+      },
+      newText: edit.newText,
+    }))
+  } catch (error) {
+    // Formatting failed (likely parse error), return empty edits
+    console.error('Formatting failed:', error)
+    return []
+  }
+}
+
+/**
+ * Format the part of a query covering a selection
+ *
+ * Only the smallest subtree covering the selection is rewritten, so the
+ * rest of the query keeps its layout. Needs the WASM formatter; without it
+ * no edits are returned.
+ *
+ * @param query - The query the selection is in
+ * @param document - The document containing the query
+ * @param range - The selection, in document positions
+ */
+export function formatQueryRange(
+  query: GroqQuery,
+  document: TextDocument,
+  range: Range,
+  options: FormattingOptions = {}
+): TextEdit[] {
+  if (!isWasmInitialized()) {
+    return []
+  }
+
+  // Both TextDocument and groq-wasm count UTF-16 code units
+  const clamp = (offset: number) => Math.min(Math.max(offset - query.start, 0), query.query.length)
+  const start = clamp(document.offsetAt(range.start))
+  const end = clamp(document.offsetAt(range.end))
+
+  try {
+    const edit = formatRange(
+      query.query,
+      start,
+      end,
+      options.printWidth ? { width: options.printWidth } : undefined
+    )
+    if (query.query.slice(edit.start, edit.end) === edit.newText) {
+      return []
+    }
+
+    return [
+      {
+        range: {
+          start: document.positionAt(query.start + edit.start),
+          end: document.positionAt(query.start + edit.end),
+        },
+        newText: edit.newText,
+      },
+    ]
+  } catch (error) {
+    // Formatting failed (likely parse error), return empty edits
+    console.error('Range formatting failed:', error)
+    return []
+  }
+}
+
 /**
  * Convert a GroqQuery to an LSP Range
  */
@@ -55,6 +55,7 @@ export {
   formatQuery,
   formatDocument,
   formatGroqFile,
+  formatQueryRange,
   type FormattingOptions,
 } from './capabilities/formatting.js'
 
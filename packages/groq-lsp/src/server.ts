@@ -19,6 +19,7 @@ import {
   type InitializeParams,
   type TextDocumentPositionParams,
   type DocumentFormattingParams,
+  type DocumentRangeFormattingParams,
   type CompletionParams,
 } from 'vscode-languageserver/node.js'
 import { TextDocument } from 'vscode-languageserver-textdocument'
@@ -29,7 +30,11 @@ import { extractQueries, findQueryAtOffset, offsetToQueryPosition } from './util
 import { computeDocumentDiagnostics } from './capabilities/diagnostics.js'
 import { getHoverInfo } from './capabilities/hover.js'
 import { getCompletions, getCompletionTriggerCharacters } from './capabilities/completion.js'
-import { formatDocument, formatGroqFile } from './capabilities/formatting.js'
+import {
+  formatDocument,
+  formatGroqFile,
+  formatQueryRange,
+} from './capabilities/formatting.js'
 import type { GroqQuery, DocumentState } from './types.js'
 import { initLinter } from '@sanity-labs/groq-lint'
 import { initWasmFormatter } from '@sanity-labs/prettier-plugin-groq'
@@ -106,6 +111,7 @@ connection.onInitialize((params: InitializeParams): InitializeResult => {
       hoverProvider: true,
       // Formatting
       documentFormattingProvider: true,
+      documentRangeFormattingProvider: true,
       // We'll compute diagnostics on document change
     },
   }
@@ -327,6 +333,39 @@ connection.onDocumentFormatting(async (params: DocumentFormattingParams) => {
   })
 })
 
+/**
+ * Range formatting handler
+ */
+connection.onDocumentRangeFormatting((params: DocumentRangeFormattingParams) => {
+  if (!globalSettings.enableFormatting) {
+    return []
+  }
+
+  const document = documents.get(params.textDocument.uri)
+  if (!document) return []
+
+  // .groq files are a single query; elsewhere, the query the selection
+  // starts in
+  const query: GroqQuery | undefined =
+    document.languageId === 'groq'
+      ? {
+          query: document.getText(),
+          start: 0,
+          end: document.getText().length,
+          line: 0,
+          column: 0,
+        }
+      : findQueryAtOffset(
+          documentStates.get(document.uri)?.queries ?? [],
+          document.offsetAt(params.range.start)
+        )
+  if (!query) return []
+
+  // params.options is not forwarded: it only carries tabSize and
+  // insertSpaces, and the WASM formatter only takes a line width
+  return formatQueryRange(query, document, params.range)
+})
+
 // Start listening
 documents.listen(connection)
 connection.listen()
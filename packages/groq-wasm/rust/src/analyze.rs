@@ -0,0 +1,249 @@
+//! Structured facts about a query: the document types it filters on, the
+//! fields it reads, the references it follows, its parameters and the
+//! functions it calls.
+//!
+//! Field reads are reported as paths relative to the scope they are read
+//! in (`slug.current`, `author->name`, `tags[]`), tagged with whether they
+//! appear in a filter, an `order()` or a projection. Reads that are not
+//! relative to a scope, such as `^._id`, are not reported.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::ast::{Node, NodeKind, Walk};
+use crate::positions::{LineIndex, Range};
+use crate::schema_rules::type_comparisons;
+
+/// Where a field is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldContext {
+    Filter,
+    Order,
+    Projection,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldRead {
+    pub path: String,
+    pub context: FieldContext,
+    pub start: usize,
+    pub end: usize,
+    pub range: Range,
+}
+
+/// A named thing at a place in the query
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Occurrence {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub range: Range,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Analysis {
+    /// Types compared against `_type` with `==` or `in`
+    pub document_types: BTreeSet<String>,
+    pub fields: Vec<FieldRead>,
+    /// Each `->`, named by the path of the reference it resolves
+    pub references: Vec<Occurrence>,
+    /// Each use of a `$parameter`, named without the `$`
+    pub parameters: Vec<Occurrence>,
+    /// Functions and pipe functions, as `name` or `namespace::name`
+    pub functions: BTreeSet<String>,
+}
+
+pub fn analyze(root: &Node, source: &str) -> Analysis {
+    let mut analyzer = Analyzer {
+        source,
+        index: LineIndex::new(source),
+        analysis: Analysis::default(),
+    };
+    analyzer.visit(root, None);
+    analyzer.analysis
+}
+
+struct Analyzer<'a> {
+    source: &'a str,
+    index: LineIndex<'a>,
+    analysis: Analysis,
+}
+
+impl Analyzer<'_> {
+    fn visit(&mut self, node: &Node, context: Option<FieldContext>) {
+        if let Some(path) = path(node) {
+            if let Some(context) = context {
+                self.analysis.fields.push(FieldRead {
+                    path,
+                    context,
+                    start: node.span.start,
+                    end: node.span.end,
+                    range: self.index.range(node.span()),
+                });
+            }
+            self.chain_references(node);
+            return;
+        }
+
+        match &node.kind {
+            NodeKind::Filter { base, expr } => {
+                self.visit(base, context);
+                self.visit(expr, Some(FieldContext::Filter));
+            }
+            NodeKind::Projection { base, expr } => {
+                self.visit(base, context);
+                self.visit(expr, Some(FieldContext::Projection));
+            }
+            NodeKind::PipeFuncCall { base, name, args } => {
+                self.analysis.functions.insert(name.clone());
+                self.visit(base, context);
+                let args_context = if name == "order" {
+                    Some(FieldContext::Order)
+                } else {
+                    context
+                };
+                for arg in args {
+                    self.visit(arg, args_context);
+                }
+            }
+            NodeKind::FuncCall {
+                namespace,
+                name,
+                args,
+            } => {
+                self.analysis.functions.insert(if namespace == "global" {
+                    name.clone()
+                } else {
+                    format!("{}::{}", namespace, name)
+                });
+                for arg in args {
+                    self.visit(arg, context);
+                }
+            }
+            NodeKind::Parameter { name } => {
+                let occurrence = self.occurrence(name.clone(), node);
+                self.analysis.parameters.push(occurrence);
+            }
+            NodeKind::Deref { base } => {
+                let name = self.source[base.span.start..base.span.end].to_string();
+                let occurrence = self.occurrence(name, node);
+                self.analysis.references.push(occurrence);
+                self.visit(base, context);
+            }
+            NodeKind::OpCall { op, left, right } => {
+                for (name, _) in type_comparisons(*op, left, right) {
+                    self.analysis.document_types.insert(name.to_string());
+                }
+                self.visit(left, context);
+                self.visit(right, context);
+            }
+            _ => {
+                for child in node.children() {
+                    self.visit(child, context);
+                }
+            }
+        }
+    }
+
+    /// Record the `->` inside a field path such as `author->company->name`
+    fn chain_references(&mut self, node: &Node) {
+        match &node.kind {
+            NodeKind::Deref { base } => {
+                let name = path(base).unwrap_or_default();
+                let occurrence = self.occurrence(name, node);
+                self.chain_references(base);
+                self.analysis.references.push(occurrence);
+            }
+            NodeKind::AccessAttribute {
+                base: Some(base), ..
+            }
+            | NodeKind::ArrayCoerce { base } => self.chain_references(base),
+            _ => {}
+        }
+    }
+
+    fn occurrence(&self, name: String, node: &Node) -> Occurrence {
+        Occurrence {
+            name,
+            start: node.span.start,
+            end: node.span.end,
+            range: self.index.range(node.span()),
+        }
+    }
+}
+
+/// Path of a field read relative to the current scope, such as
+/// `author->name` or `tags[]`
+fn path(node: &Node) -> Option<String> {
+    match &node.kind {
+        NodeKind::AccessAttribute { base: None, name } => Some(name.clone()),
+        NodeKind::AccessAttribute {
+            base: Some(base),
+            name,
+        } => {
+            let base = path(base)?;
+            let dot = if base.ends_with("->") { "" } else { "." };
+            Some(format!("{}{}{}", base, dot, name))
+        }
+        NodeKind::ArrayCoerce { base } => Some(format!("{}[]", path(base)?)),
+        NodeKind::Deref { base } => Some(format!("{}->", path(base)?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    #[test]
+    fn test_analyze() {
+        let query = "*[_type in [\"post\", \"page\"] && slug.current == $slug] | order(publishedAt desc) {\n  title,\n  \"author\": author->name,\n  \"tags\": tags[]->{ title },\n  \"n\": count(*[references(^._id)])\n}[0...$limit]";
+        let analysis = analyze(&parse(query).unwrap(), query);
+        let json = serde_json::to_value(&analysis).unwrap();
+
+        assert_eq!(json["documentTypes"], serde_json::json!(["page", "post"]));
+        assert_eq!(
+            json["functions"],
+            serde_json::json!(["count", "order", "references"])
+        );
+
+        let fields: Vec<(&str, FieldContext)> = analysis
+            .fields
+            .iter()
+            .map(|f| (f.path.as_str(), f.context))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("_type", FieldContext::Filter),
+                ("slug.current", FieldContext::Filter),
+                ("publishedAt", FieldContext::Order),
+                ("title", FieldContext::Projection),
+                ("author->name", FieldContext::Projection),
+                ("tags[]->", FieldContext::Projection),
+                ("title", FieldContext::Projection),
+            ]
+        );
+
+        let references: Vec<&str> = analysis
+            .references
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(references, vec!["author", "tags[]"]);
+
+        let parameters: Vec<&str> = analysis
+            .parameters
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(parameters, vec!["slug", "limit"]);
+        assert_eq!(analysis.parameters[0].range.start.line, 1);
+    }
+}
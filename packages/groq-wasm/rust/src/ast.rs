@@ -0,0 +1,112 @@
+//! GROQ syntax tree, as produced by groq-parser.
+//!
+//! The wrapper doesn't keep a grammar of its own: groq-lint lints the tree
+//! groq-parser builds, and the native rules, `analyze`, `evaluate` and
+//! friends walk that same tree, so they can never disagree about what a
+//! query means. Node names follow groq-js (`AccessAttribute`, `OpCall`,
+//! `Deref`, ...) so tooling written against groq-js carries over.
+//!
+//! # JSON schema
+//!
+//! `parse()` serializes a [`SyntaxTree`]: `{ "version": 1, "root": <node> }`.
+//! `root` is groq-parser's own serde form of the tree: every node is an
+//! object with a `type` tag, the fields of its kind, and a `span` of UTF-8
+//! byte offsets (`{ "start": 0, "end": 5 }`, end exclusive).
+//!
+//! `AST_VERSION` is bumped whenever groq-parser renames or removes a node
+//! type or field, or changes its meaning. Adding node types or optional
+//! fields does not bump it, so consumers should ignore what they don't
+//! recognize.
+
+use serde::Serialize;
+
+pub use groq_parser::ast::{ArrayElement, Node, NodeKind, ObjectAttribute, OpKind};
+
+use crate::error::GroqError;
+use crate::positions::Span;
+
+/// Version of the serialized node schema
+pub const AST_VERSION: u32 = 1;
+
+/// Root of a serialized parse result
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyntaxTree {
+    pub version: u32,
+    pub root: Node,
+}
+
+/// Parse a query with groq-parser
+pub fn parse(query: &str) -> Result<Node, GroqError> {
+    groq_parser::parse(query).map_err(|e| GroqError::parse(e, query))
+}
+
+/// Traversal helpers the wrapper needs on groq-parser's nodes
+pub trait Walk {
+    /// Source range of the node
+    fn span(&self) -> Span;
+
+    /// Immediate child nodes, in source order
+    fn children(&self) -> Vec<&Node>;
+
+    /// Visit this node and all of its descendants, depth first
+    fn walk<'a>(&'a self, visit: &mut impl FnMut(&'a Node));
+}
+
+impl Walk for Node {
+    fn span(&self) -> Span {
+        Span::new(self.span.start, self.span.end)
+    }
+
+    fn children(&self) -> Vec<&Node> {
+        match &self.kind {
+            NodeKind::Everything
+            | NodeKind::This
+            | NodeKind::Parent { .. }
+            | NodeKind::Parameter { .. }
+            | NodeKind::Value { .. } => Vec::new(),
+            NodeKind::AccessAttribute { base, .. } => base.iter().map(|b| b.as_ref()).collect(),
+            NodeKind::AccessElement { base, index } => vec![&**base, &**index],
+            NodeKind::Slice {
+                base, left, right, ..
+            } => vec![&**base, &**left, &**right],
+            NodeKind::Filter { base, expr } | NodeKind::Projection { base, expr } => {
+                vec![&**base, &**expr]
+            }
+            NodeKind::ArrayCoerce { base }
+            | NodeKind::Deref { base }
+            | NodeKind::Not { base }
+            | NodeKind::Neg { base }
+            | NodeKind::Pos { base }
+            | NodeKind::Asc { base }
+            | NodeKind::Desc { base }
+            | NodeKind::Group { base } => vec![&**base],
+            NodeKind::PipeFuncCall { base, args, .. } => {
+                std::iter::once(base.as_ref()).chain(args.iter()).collect()
+            }
+            NodeKind::FuncCall { args, .. } => args.iter().collect(),
+            NodeKind::Object { attributes } => attributes
+                .iter()
+                .flat_map(|attr| match attr {
+                    ObjectAttribute::Value { value, .. } => vec![value],
+                    ObjectAttribute::Splat { value, .. } => value.iter().collect(),
+                    ObjectAttribute::Conditional {
+                        condition, value, ..
+                    } => vec![condition, value],
+                })
+                .collect(),
+            NodeKind::Array { elements } => elements.iter().map(|e| &e.value).collect(),
+            NodeKind::OpCall { left, right, .. }
+            | NodeKind::And { left, right }
+            | NodeKind::Or { left, right }
+            | NodeKind::Range { left, right, .. }
+            | NodeKind::Pair { left, right } => vec![&**left, &**right],
+        }
+    }
+
+    fn walk<'a>(&'a self, visit: &mut impl FnMut(&'a Node)) {
+        visit(self);
+        for child in self.children() {
+            child.walk(visit);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Keeping `//` comments through formatting.
+//!
+//! groq-format drops comments, so they are put back into its output. Each
+//! comment is attached to a piece of the input (see `trivia`): a comment
+//! that shares a line with the piece before it trails that piece, any other
+//! comment leads the piece after it. The pieces of the input and the output
+//! are aligned with a diff, and each comment is re-inserted next to its
+//! piece in the output. A comment whose piece the formatter removed (such
+//! as a trailing comma) moves to the nearest piece that is still there, so
+//! no comment is ever dropped.
+
+use crate::diff::diff;
+use crate::format_range::line_indent;
+use crate::trivia::{scan, Piece, PieceKind};
+
+/// groq-format's indentation unit
+const INDENT: &str = "  ";
+
+/// Where a comment goes, relative to the significant pieces of a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    /// On the line of the piece, after it
+    After(usize),
+    /// On its own line before the piece
+    Before(usize),
+    /// After everything else
+    End,
+}
+
+struct Comment<'a> {
+    text: &'a str,
+    anchor: Anchor,
+}
+
+/// Re-insert the comments of `input` into `formatted`
+pub fn restore(input: &str, formatted: &str) -> String {
+    let input_pieces = scan(input);
+    let output_pieces = scan(formatted);
+
+    let comments = comments(input, &input_pieces);
+    let kept: Vec<&str> = output_pieces
+        .iter()
+        .filter(|t| t.kind == PieceKind::Comment)
+        .map(|t| t.text(formatted).trim_end())
+        .collect();
+    if comments.iter().map(|c| c.text).eq(kept) {
+        return formatted.to_string();
+    }
+
+    let input_significant = significant_texts(input, &input_pieces);
+    let output_significant: Vec<&Piece> = output_pieces.iter().filter(|t| !t.is_trivia()).collect();
+    let output_texts: Vec<&str> = output_significant
+        .iter()
+        .map(|t| t.text(formatted))
+        .collect();
+
+    // Input piece index → output piece index, for pieces the formatter kept
+    let mut aligned = vec![None; input_significant.len()];
+    let (mut i, mut j) = (0, 0);
+    for change in diff(&input_significant, &output_texts) {
+        while i < change.old.start {
+            aligned[i] = Some(j);
+            i += 1;
+            j += 1;
+        }
+        i = change.old.end;
+        j = change.new.end;
+    }
+    while i < aligned.len() {
+        aligned[i] = Some(j);
+        i += 1;
+        j += 1;
+    }
+
+    let mut insertions: Vec<(usize, String)> = comments
+        .iter()
+        .map(|comment| {
+            let anchor = resolve(comment.anchor, &aligned);
+            insertion(formatted, &output_significant, anchor, comment.text)
+        })
+        .collect();
+    // Comments at the same offset keep their order: inserting the last one
+    // first leaves the earlier ones in front of it
+    insertions.sort_by_key(|(offset, _)| *offset);
+
+    let mut output = formatted.to_string();
+    for (offset, text) in insertions.iter().rev() {
+        output.insert_str(*offset, text);
+    }
+
+    trim_line_ends(&output)
+}
+
+/// Comments of the input with the piece each is attached to
+fn comments<'a>(input: &'a str, pieces: &[Piece]) -> Vec<Comment<'a>> {
+    let mut comments = Vec::new();
+    let mut significant = 0;
+    let mut line_break = true;
+
+    for piece in pieces {
+        match piece.kind {
+            PieceKind::Whitespace => line_break |= piece.text(input).contains('\n'),
+            PieceKind::Comment => comments.push(Comment {
+                text: piece.text(input).trim_end(),
+                anchor: if line_break {
+                    Anchor::Before(significant)
+                } else {
+                    Anchor::After(significant - 1)
+                },
+            }),
+            _ => {
+                significant += 1;
+                line_break = false;
+            }
+        }
+    }
+
+    comments
+}
+
+fn significant_texts<'a>(source: &'a str, pieces: &[Piece]) -> Vec<&'a str> {
+    pieces
+        .iter()
+        .filter(|t| !t.is_trivia())
+        .map(|t| t.text(source))
+        .collect()
+}
+
+/// Map an input anchor to the output, moving it to the nearest kept piece
+/// if its own piece is gone
+fn resolve(anchor: Anchor, aligned: &[Option<usize>]) -> Anchor {
+    let before = |i: usize| {
+        aligned[..i]
+            .iter()
+            .rev()
+            .find_map(|j| *j)
+            .map(Anchor::After)
+    };
+    let after = |i: usize| aligned[i..].iter().find_map(|j| *j).map(Anchor::Before);
+
+    match anchor {
+        Anchor::After(i) => aligned[i]
+            .map(Anchor::After)
+            .or_else(|| before(i))
+            .or_else(|| after(i)),
+        Anchor::Before(i) if i < aligned.len() => aligned[i]
+            .map(Anchor::Before)
+            .or_else(|| after(i))
+            .or_else(|| before(i)),
+        _ => None,
+    }
+    .unwrap_or(Anchor::End)
+}
+
+/// Offset in `formatted` and the text to insert there for one comment
+fn insertion(formatted: &str, pieces: &[&Piece], anchor: Anchor, comment: &str) -> (usize, String) {
+    match anchor {
+        Anchor::After(j) => {
+            // Keep a comma that follows the piece in front of the comment
+            let piece = match pieces.get(j + 1) {
+                Some(next)
+                    if next.text(formatted) == "," && next.span.start == pieces[j].span.end =>
+                {
+                    next
+                }
+                _ => pieces[j],
+            };
+            let offset = piece.span.end;
+            let rest = formatted[offset..].split('\n').next().unwrap_or_default();
+            if rest.trim().is_empty() {
+                (offset, format!(" {}", comment))
+            } else {
+                // The rest of the line moves down so the comment can't
+                // swallow it
+                let code = offset + rest.len() - rest.trim_start().len();
+                let space = if code == offset { " " } else { "" };
+                let (indent, _) = line_indent(formatted, offset);
+                (code, format!("{}{}\n{}", space, comment, indent))
+            }
+        }
+        Anchor::Before(j) => {
+            let offset = pieces[j].span.start;
+            let (indent, column) = line_indent(formatted, offset);
+            if column == indent.chars().count() {
+                (offset, format!("{}\n{}", comment, indent))
+            } else {
+                // The piece is inside something opened earlier on its line,
+                // such as a filter, so it moves down one level deeper
+                let indent = format!("{}{}", indent, INDENT);
+                (offset, format!("\n{}{}\n{}", indent, comment, indent))
+            }
+        }
+        Anchor::End if formatted.is_empty() || formatted.ends_with('\n') => {
+            (formatted.len(), comment.to_string())
+        }
+        Anchor::End => (formatted.len(), format!("\n{}", comment)),
+    }
+}
+
+/// Drop spaces left at the end of lines that were split for a comment
+fn trim_line_ends(source: &str) -> String {
+    scan(source)
+        .iter()
+        .map(|piece| {
+            let text = piece.text(source);
+            if piece.kind == PieceKind::Whitespace && text.contains('\n') {
+                let mut lines: Vec<&str> = text.split('\n').collect();
+                let last = lines.len() - 1;
+                for line in &mut lines[..last] {
+                    *line = line.trim_end_matches([' ', '\t']);
+                }
+                lines.join("\n")
+            } else {
+                text.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment_texts(source: &str) -> Vec<String> {
+        scan(source)
+            .iter()
+            .filter(|t| t.kind == PieceKind::Comment)
+            .map(|t| t.text(source).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_restore_leading_and_trailing() {
+        let input =
+            "*[\n  // Only posts\n  _type == \"post\"\n]{\n  title, // Shown in lists\n  body\n}";
+        let formatted = "*[_type == \"post\"]{\n  title,\n  body\n}";
+        let result = restore(input, formatted);
+        assert_eq!(
+            result,
+            "*[\n  // Only posts\n  _type == \"post\"]{\n  title, // Shown in lists\n  body\n}"
+        );
+    }
+
+    #[test]
+    fn test_restore_never_drops_comments() {
+        // The comment's own line is joined with code that follows it, and
+        // the comma it trails is gone
+        let input = "*{\n  a,\n  b, // last\n} // done";
+        let formatted = "*{ a, b }";
+        let result = restore(input, formatted);
+        assert_eq!(comment_texts(&result), comment_texts(input));
+        assert_eq!(result, "*{ a, b // last\n} // done");
+
+        let input = "// Everything\n*";
+        assert_eq!(restore(input, "*"), input);
+    }
+}
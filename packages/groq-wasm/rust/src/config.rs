@@ -0,0 +1,203 @@
+//! Lint configuration passed in from JavaScript.
+//!
+//! Rule ids may be given in either the Rust (`deep_pagination`) or the
+//! ESLint (`deep-pagination`) form. Each rule accepts either a boolean or an
+//! options object:
+//!
+//! ```json
+//! {
+//!   "rules": {
+//!     "join-in-filter": false,
+//!     "deep_pagination": { "severity": "high", "threshold": 500 },
+//!     "large_order": { "threshold": 5000 }
+//!   },
+//!   "positions": true,
+//!   "params": { "slug": "hello-world", "limit": 10 },
+//!   "stats": { "documents": 1200, "types": { ... } }
+//! }
+//! ```
+//!
+//! A `threshold` is accepted by `deep_pagination` and `many_joins` (see
+//! `thresholds`) and by the stats rules, and rejected for any other rule.
+//!
+//! `positions` adds a `range` with UTF-16 and line/column positions to
+//! every finding and suggestion. `params` are the values the query will be
+//! sent with; supplying them enables the param rules (see `param_rules`).
+//! `stats` are dataset statistics from `datasetStats()`; supplying them
+//! enables the stats rules (see `stats_rules`).
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::stats::DatasetStats;
+use crate::stats_rules;
+use crate::thresholds;
+
+/// Severity levels matching Rust groq-lint.
+///
+/// The ESLint-style names are accepted as aliases on input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[serde(alias = "error")]
+    High,
+    #[serde(alias = "warning", alias = "warn")]
+    Medium,
+    #[serde(alias = "info")]
+    Low,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+        }
+    }
+}
+
+/// Options for a single rule
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuleOptions {
+    pub enabled: Option<bool>,
+    pub severity: Option<Severity>,
+    /// Rule-specific limit, e.g. the document count for `large_order`
+    pub threshold: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum RuleSetting {
+    Enabled(bool),
+    Options(RuleOptions),
+}
+
+impl From<RuleSetting> for RuleOptions {
+    fn from(setting: RuleSetting) -> Self {
+        match setting {
+            RuleSetting::Enabled(enabled) => RuleOptions {
+                enabled: Some(enabled),
+                ..RuleOptions::default()
+            },
+            RuleSetting::Options(options) => options,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RawLintConfig {
+    #[serde(default)]
+    rules: HashMap<String, RuleSetting>,
+    #[serde(default)]
+    positions: bool,
+    params: Option<BTreeMap<String, Value>>,
+    stats: Option<DatasetStats>,
+}
+
+/// Lint configuration with rule ids normalized to snake_case
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    rules: HashMap<String, RuleOptions>,
+    /// Add UTF-16 and line/column positions to findings
+    pub positions: bool,
+    /// Params the query will be run with, keyed by name without `$`
+    pub params: Option<BTreeMap<String, Value>>,
+    /// Statistics of the dataset the query will run against
+    pub stats: Option<DatasetStats>,
+}
+
+impl LintConfig {
+    /// Parse a config from its JSON form. `None` yields the default config.
+    pub fn from_json(json: Option<&str>) -> Result<Self, String> {
+        let Some(json) = json else {
+            return Ok(LintConfig::default());
+        };
+
+        let raw: RawLintConfig = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let rules: HashMap<String, RuleOptions> = raw
+            .rules
+            .into_iter()
+            .map(|(id, setting)| (normalize_rule_id(&id), RuleOptions::from(setting)))
+            .collect();
+
+        for (id, options) in &rules {
+            let takes_threshold = thresholds::default_threshold(id).is_some()
+                || stats_rules::default_threshold(id).is_some();
+            if options.threshold.is_some() && !takes_threshold {
+                return Err(format!("rule `{}` does not take a threshold", id));
+            }
+        }
+
+        Ok(LintConfig {
+            rules,
+            positions: raw.positions,
+            params: raw.params,
+            stats: raw.stats,
+        })
+    }
+
+    pub fn is_enabled(&self, rule_id: &str) -> bool {
+        self.options(rule_id)
+            .and_then(|o| o.enabled)
+            .unwrap_or(true)
+    }
+
+    pub fn severity(&self, rule_id: &str) -> Option<Severity> {
+        self.options(rule_id).and_then(|o| o.severity)
+    }
+
+    pub fn threshold(&self, rule_id: &str) -> Option<u64> {
+        self.options(rule_id).and_then(|o| o.threshold)
+    }
+
+    fn options(&self, rule_id: &str) -> Option<&RuleOptions> {
+        self.rules.get(rule_id)
+    }
+}
+
+/// Convert a kebab-case rule id to the snake_case form groq-lint uses
+pub fn normalize_rule_id(rule_id: &str) -> String {
+    rule_id.replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_accepts_both_id_forms() {
+        let config = LintConfig::from_json(Some(
+            r#"{"rules": {"join-in-filter": false, "large_order": {"severity": "error", "threshold": 500}}}"#,
+        ))
+        .unwrap();
+
+        assert!(!config.is_enabled("join_in_filter"));
+        assert!(config.is_enabled("large_order"));
+        assert_eq!(config.severity("large_order"), Some(Severity::High));
+        assert_eq!(config.threshold("large_order"), Some(500));
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_options() {
+        assert!(LintConfig::from_json(Some(r#"{"rules": {"many_joins": {"limit": 3}}}"#)).is_err());
+        assert!(
+            LintConfig::from_json(Some(r#"{"rules": {"large_pages": {"threshold": 3}}}"#)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_config_accepts_groq_lint_thresholds() {
+        let config = LintConfig::from_json(Some(
+            r#"{"rules": {"many-joins": {"threshold": 3}, "deep_pagination": {"threshold": 200}}}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(config.threshold("many_joins"), Some(3));
+        assert_eq!(config.threshold("deep_pagination"), Some(200));
+    }
+}
@@ -0,0 +1,89 @@
+//! In-memory documents loaded from a `sanity dataset export` NDJSON file.
+//!
+//! Each non-empty line is one document, which must be a JSON object with a
+//! string `_id`. Documents keep their file order, which is the order `*`
+//! yields them in, and are indexed by `_id` for dereferencing.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    documents: Vec<Value>,
+    by_id: HashMap<String, usize>,
+}
+
+impl Dataset {
+    pub fn from_ndjson(ndjson: &str) -> Result<Self, String> {
+        let mut dataset = Dataset::default();
+
+        for (line_number, line) in ndjson.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let document: Value = serde_json::from_str(line)
+                .map_err(|e| format!("line {}: {}", line_number + 1, e))?;
+            dataset
+                .insert(document)
+                .map_err(|e| format!("line {}: {}", line_number + 1, e))?;
+        }
+
+        Ok(dataset)
+    }
+
+    /// Build a dataset from documents that are already parsed
+    pub fn from_documents(documents: Vec<Value>) -> Result<Self, String> {
+        let mut dataset = Dataset::default();
+        for (index, document) in documents.into_iter().enumerate() {
+            dataset
+                .insert(document)
+                .map_err(|e| format!("document {}: {}", index + 1, e))?;
+        }
+        Ok(dataset)
+    }
+
+    /// Add a document; a later document with the same `_id` replaces the
+    /// earlier one
+    fn insert(&mut self, document: Value) -> Result<(), String> {
+        let Some(id) = document.get("_id").and_then(Value::as_str) else {
+            return Err("document has no string _id".to_string());
+        };
+        match self.by_id.get(id) {
+            Some(index) => self.documents[*index] = document,
+            None => {
+                self.by_id.insert(id.to_string(), self.documents.len());
+                self.documents.push(document);
+            }
+        }
+        Ok(())
+    }
+
+    /// All documents, in file order
+    pub fn documents(&self) -> &[Value] {
+        &self.documents
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Value> {
+        self.by_id.get(id).map(|index| &self.documents[*index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_ndjson() {
+        let dataset = Dataset::from_ndjson(
+            "{\"_id\": \"a\", \"_type\": \"post\"}\n\n{\"_id\": \"b\"}\n{\"_id\": \"a\", \"_type\": \"page\"}\n",
+        )
+        .unwrap();
+        assert_eq!(dataset.documents().len(), 2);
+        assert_eq!(dataset.get("a").unwrap()["_type"], "page");
+
+        let error = Dataset::from_ndjson("{\"_id\": \"a\"}\n{\"title\": \"x\"}").unwrap_err();
+        assert_eq!(error, "line 2: document has no string _id");
+    }
+}
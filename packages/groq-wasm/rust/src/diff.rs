@@ -0,0 +1,342 @@
+//! Myers' O(ND) difference algorithm over arbitrary sequences.
+//!
+//! Used to turn a formatted query back into minimal edits against the
+//! input (diffing tokens) and to render unified diffs (diffing lines).
+//! This is the linear-space variant: rather than keeping every step of the
+//! search to walk back through, it finds the middle of a shortest edit path
+//! by searching from both ends and recurses on the two halves.
+
+use std::ops::Range;
+
+/// A run of `old` replaced by a run of `new`; either may be empty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// The changes turning `old` into `new`, in order
+pub fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    for step in edit_script(old, new) {
+        let (dx, dy) = match step {
+            Step::Equal => {
+                x += 1;
+                y += 1;
+                continue;
+            }
+            Step::Delete => (1, 0),
+            Step::Insert => (0, 1),
+        };
+
+        match changes.last_mut() {
+            Some(change) if change.old.end == x && change.new.end == y => {
+                change.old.end += dx;
+                change.new.end += dy;
+            }
+            _ => changes.push(Change {
+                old: x..x + dx,
+                new: y..y + dy,
+            }),
+        }
+        x += dx;
+        y += dy;
+    }
+
+    changes
+}
+
+/// Unified diff of two texts, line by line, with `context` unchanged lines
+/// around each hunk. Empty if the texts are equal.
+///
+/// Lines are compared with their terminators, so a change of line ending
+/// or of the final newline is a change like any other. As in GNU diff, a
+/// last line without a newline is followed by a `\ No newline at end of
+/// file` marker.
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str, context: usize) -> String {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let changes = diff(&a, &b);
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let mut hunk_start = 0;
+    while hunk_start < changes.len() {
+        // Changes closer than twice the context share a hunk
+        let mut hunk_end = hunk_start + 1;
+        while hunk_end < changes.len()
+            && changes[hunk_end].old.start - changes[hunk_end - 1].old.end <= 2 * context
+        {
+            hunk_end += 1;
+        }
+        let hunk = &changes[hunk_start..hunk_end];
+        let (first, last) = (&hunk[0], &hunk[hunk.len() - 1]);
+
+        let old_start = first.old.start.saturating_sub(context);
+        let old_end = (last.old.end + context).min(a.len());
+        let new_start = first.new.start - (first.old.start - old_start);
+        let new_end = last.new.end + (old_end - last.old.end);
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end),
+            hunk_range(new_start, new_end)
+        ));
+
+        let mut at = old_start;
+        for change in hunk {
+            for line in &a[at..change.old.start] {
+                push_line(&mut out, ' ', line);
+            }
+            for line in &a[change.old.clone()] {
+                push_line(&mut out, '-', line);
+            }
+            for line in &b[change.new.clone()] {
+                push_line(&mut out, '+', line);
+            }
+            at = change.old.end;
+        }
+        for line in &a[at..old_end] {
+            push_line(&mut out, ' ', line);
+        }
+
+        hunk_start = hunk_end;
+    }
+    out
+}
+
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// `start,len` of a hunk side, 1-based; an empty side names the line
+/// before it
+fn hunk_range(start: usize, end: usize) -> String {
+    match end - start {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        len => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Shortest edit script from `a` to `b`, in forward order
+fn edit_script<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Step> {
+    let mut steps = Vec::with_capacity(a.len() + b.len());
+    push_script(a, b, &mut steps);
+    steps
+}
+
+fn push_script<T: PartialEq>(a: &[T], b: &[T], steps: &mut Vec<Step>) {
+    // The common prefix and suffix are cheap to strip and usually make up
+    // most of the input
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a = &a[prefix..a.len() - suffix];
+    let b = &b[prefix..b.len() - suffix];
+
+    steps.extend(std::iter::repeat_n(Step::Equal, prefix));
+    match middle(a, b) {
+        Some((x, y)) => {
+            push_script(&a[..x], &b[..y], steps);
+            push_script(&a[x..], &b[y..], steps);
+        }
+        None => {
+            steps.extend(std::iter::repeat_n(Step::Delete, a.len()));
+            steps.extend(std::iter::repeat_n(Step::Insert, b.len()));
+        }
+    }
+    steps.extend(std::iter::repeat_n(Step::Equal, suffix));
+}
+
+/// A point a shortest edit path from `a` to `b` passes through, splitting
+/// it into two shorter problems. `None` if one side is empty, or if the
+/// searches never meet, in which case everything is replaced.
+///
+/// `a` and `b` must not share a prefix or suffix, so the path has at least
+/// two edits and the point found is strictly inside it.
+fn middle<T: PartialEq>(a: &[T], b: &[T]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    if n == 0 || m == 0 {
+        return None;
+    }
+
+    // forward[offset + k] is the furthest x reached on diagonal k = x - y
+    // from the start; backward[offset + k] the same from the end, with x
+    // and y counted back from n and m
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let len = 2 * max_d + 2;
+    let mut forward = vec![-1; len as usize];
+    let mut backward = vec![-1; len as usize];
+    forward[offset as usize + 1] = 0;
+    backward[offset as usize + 1] = 0;
+
+    // Paths meet forward-to-backward when the diagonals differ by an odd
+    // amount, and backward-to-forward otherwise
+    let delta = n - m;
+    let front = delta % 2 != 0;
+    // Diagonals at either end that have left the grid
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    let furthest = |v: &[isize], d: isize, k: isize| {
+        let at = |k: isize| v[(offset + k) as usize];
+        if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            at(k + 1)
+        } else {
+            at(k - 1) + 1
+        }
+    };
+
+    for d in 0..max_d {
+        for k1 in (-d + k1_start..=d - k1_end).step_by(2) {
+            let mut x1 = furthest(&forward, d, k1);
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[(offset + k1) as usize] = x1;
+
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if front {
+                let k2 = offset + delta - k1;
+                if (0..len).contains(&k2) && backward[k2 as usize] != -1 {
+                    let x2 = n - backward[k2 as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+        }
+
+        for k2 in (-d + k2_start..=d - k2_end).step_by(2) {
+            let mut x2 = furthest(&backward, d, k2);
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            backward[(offset + k2) as usize] = x2;
+
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !front {
+                let k1 = offset + delta - k2;
+                if (0..len).contains(&k1) && forward[k1 as usize] != -1 {
+                    let x1 = forward[k1 as usize];
+                    let y1 = offset + x1 - k1;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(old: &str, new: &str) -> String {
+        let old: Vec<char> = old.chars().collect();
+        let new: Vec<char> = new.chars().collect();
+        let mut result = String::new();
+        let mut at = 0;
+        for change in diff(&old, &new) {
+            result.extend(&old[at..change.old.start]);
+            result.extend(&new[change.new.clone()]);
+            at = change.old.end;
+        }
+        result.extend(&old[at..]);
+        result
+    }
+
+    #[test]
+    fn test_diff_round_trips() {
+        for (old, new) in [
+            ("abcabba", "cbabac"),
+            ("", "abc"),
+            ("abc", ""),
+            ("same", "same"),
+            ("*{a,b}", "*{ a, b }"),
+        ] {
+            assert_eq!(apply(old, new), new, "{} -> {}", old, new);
+        }
+    }
+
+    #[test]
+    fn test_diff_long_inputs() {
+        // Long enough that a quadratic trace would be noticeable
+        let old: String = (0..2000)
+            .map(|i| if i % 7 == 0 { 'x' } else { 'a' })
+            .collect();
+        let new: String = (0..2000)
+            .map(|i| if i % 5 == 0 { 'y' } else { 'a' })
+            .collect();
+        assert_eq!(apply(&old, &new), new);
+
+        // Shortest script: same number of edits as a brute-force LCS
+        let (a, b): (Vec<char>, Vec<char>) =
+            ("abcabba".chars().collect(), "cbabac".chars().collect());
+        let edits: usize = diff(&a, &b).iter().map(|c| c.old.len() + c.new.len()).sum();
+        assert_eq!(edits, 5);
+    }
+
+    #[test]
+    fn test_diff_is_minimal() {
+        let old: Vec<char> = "*{a,b}".chars().collect();
+        let new: Vec<char> = "*{ a, b }".chars().collect();
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().all(|c| c.old.is_empty() && c.new.len() == 1));
+    }
+
+    #[test]
+    fn test_unified() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\n";
+        assert_eq!(
+            unified(old, new, "old", "new", 1),
+            "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n@@ -9,2 +9 @@\n i\n-j\n"
+        );
+        assert_eq!(unified(old, old, "old", "new", 3), "");
+    }
+
+    #[test]
+    fn test_unified_line_endings() {
+        assert_eq!(
+            unified("a\nb\n", "a\nb", "old", "new", 1),
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n"
+        );
+        assert_eq!(
+            unified("a\r\nb", "a\nb", "old", "new", 0),
+            "--- old\n+++ new\n@@ -1 +1 @@\n-a\r\n+a\n"
+        );
+    }
+}
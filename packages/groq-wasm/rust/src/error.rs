@@ -0,0 +1,177 @@
+//! Errors returned across the WASM boundary.
+//!
+//! Every export fails with a `GroqError` rather than a bare string so the
+//! JavaScript side can switch on `code` and highlight `start..end` instead
+//! of pattern-matching on messages.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::evaluate::EvalError;
+use crate::positions::Span;
+use crate::trivia;
+
+/// Stable error codes. These are part of the public API: add new ones,
+/// never rename existing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The query is not valid GROQ
+    ParseError,
+    /// The rule configuration could not be read
+    InvalidConfig,
+    /// The schema JSON could not be read
+    InvalidSchema,
+    /// Offsets passed in are out of bounds or not on a character boundary
+    InvalidRange,
+    /// The NDJSON dataset could not be read
+    InvalidDataset,
+    /// The query is valid but could not be evaluated
+    EvaluationError,
+    /// Anything else; indicates a bug in the wrapper or upstream crates
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::ParseError => "PARSE_ERROR",
+            ErrorCode::InvalidConfig => "INVALID_CONFIG",
+            ErrorCode::InvalidSchema => "INVALID_SCHEMA",
+            ErrorCode::InvalidRange => "INVALID_RANGE",
+            ErrorCode::InvalidDataset => "INVALID_DATASET",
+            ErrorCode::EvaluationError => "EVALUATION_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroqError {
+    code: ErrorCode,
+    message: String,
+    span: Option<Span>,
+}
+
+impl GroqError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        GroqError {
+            code,
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    /// A groq-parser error, spanning the token the parser stopped at, or
+    /// empty at the end of the query when it ran out of input
+    pub fn parse(e: groq_parser::ParseError, query: &str) -> Self {
+        let end = Span::new(query.len(), query.len());
+        let span = trivia::scan(query)
+            .into_iter()
+            .find(|piece| piece.span.end > e.pos && !piece.is_trivia())
+            .map_or(end, |piece| piece.span);
+
+        GroqError {
+            code: ErrorCode::ParseError,
+            message: e.message,
+            span: Some(span),
+        }
+    }
+
+    pub fn config(message: impl std::fmt::Display) -> Self {
+        GroqError::new(
+            ErrorCode::InvalidConfig,
+            format!("Config error: {}", message),
+        )
+    }
+
+    pub fn schema(message: impl std::fmt::Display) -> Self {
+        GroqError::new(
+            ErrorCode::InvalidSchema,
+            format!("Schema error: {}", message),
+        )
+    }
+
+    pub fn dataset(message: impl std::fmt::Display) -> Self {
+        GroqError::new(
+            ErrorCode::InvalidDataset,
+            format!("Dataset error: {}", message),
+        )
+    }
+
+    pub fn internal(message: impl std::fmt::Display) -> Self {
+        GroqError::new(ErrorCode::InternalError, message.to_string())
+    }
+}
+
+#[wasm_bindgen]
+impl GroqError {
+    /// Stable error code, e.g. `PARSE_ERROR`
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> String {
+        self.code.as_str().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    /// Start byte offset of the offending token, if known
+    #[wasm_bindgen(getter)]
+    pub fn start(&self) -> Option<usize> {
+        self.span.map(|s| s.start)
+    }
+
+    /// End byte offset of the offending token, if known
+    #[wasm_bindgen(getter)]
+    pub fn end(&self) -> Option<usize> {
+        self.span.map(|s| s.end)
+    }
+}
+
+impl From<EvalError> for GroqError {
+    fn from(e: EvalError) -> Self {
+        GroqError {
+            code: ErrorCode::EvaluationError,
+            message: e.message,
+            span: Some(e.span),
+        }
+    }
+}
+
+impl std::fmt::Display for GroqError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code.as_str(), self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_spans_offending_token() {
+        let query = "*[_type == \"post\"]{ title";
+        let error = GroqError::parse(groq_parser::parse(query).unwrap_err(), query);
+        assert_eq!(error.code(), "PARSE_ERROR");
+        assert_eq!(error.start(), Some(query.len()));
+        assert_eq!(error.end(), Some(query.len()));
+
+        let query = "*[_type == \"post\"]{ title, ] }";
+        let error = GroqError::parse(groq_parser::parse(query).unwrap_err(), query);
+        let start = query.find(']').unwrap() + 1;
+        let start = start + query[start..].find(']').unwrap();
+        assert_eq!(error.start(), Some(start));
+        assert_eq!(error.end(), Some(start + 1));
+    }
+
+    #[test]
+    fn test_error_serializes_with_code() {
+        let json = serde_json::to_value(GroqError::config("bad")).unwrap();
+        assert_eq!(json["code"], "INVALID_CONFIG");
+        assert_eq!(json["message"], "Config error: bad");
+    }
+}
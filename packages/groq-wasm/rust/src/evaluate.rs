@@ -0,0 +1,930 @@
+//! A GROQ evaluator over an in-memory dataset, for testing queries offline.
+//!
+//! It covers what queries in application code commonly use: filters,
+//! projections (with splats and conditional splats), `->`, `order()`,
+//! slices and element access, the comparison, boolean and arithmetic
+//! operators, `in` (arrays and ranges), `match`, and these functions:
+//!
+//! - global: `count`, `defined`, `length`, `coalesce`, `select`, `round`,
+//!   `string`, `lower`, `upper`, `references`
+//! - `array::join`, `array::compact`, `array::unique`
+//! - `math::sum`, `math::avg`, `math::min`, `math::max`
+//! - `string::startsWith`, `string::split`
+//! - `pt::text`
+//!
+//! Anything else fails with an error naming it rather than giving a result
+//! Content Lake wouldn't. Like Content Lake, projected attributes whose
+//! value is `null` are left out of the result. After an array traversal
+//! (`*`, a filter, a slice or `[]`), attribute access and `->` apply to
+//! each element (`tags[].name`), while on a plain array they give `null`
+//! (`tags.name`).
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::ast::{Node, NodeKind, ObjectAttribute, OpKind, Walk};
+use crate::dataset::Dataset;
+use crate::infer::implicit_name;
+use crate::positions::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// Evaluate a parsed query against `dataset`
+pub fn evaluate(
+    root: &Node,
+    params: &BTreeMap<String, Value>,
+    dataset: &Dataset,
+) -> Result<Value, EvalError> {
+    let evaluator = Evaluator { params, dataset };
+    evaluator.eval(
+        root,
+        &Scope {
+            value: &Value::Null,
+            parent: None,
+        },
+    )
+}
+
+/// The value of `@`, and the scopes `^`, `^.^`, ... refer to
+struct Scope<'s> {
+    value: &'s Value,
+    parent: Option<&'s Scope<'s>>,
+}
+
+impl<'s> Scope<'s> {
+    fn nested<'t>(&'t self, value: &'t Value) -> Scope<'t> {
+        Scope {
+            value,
+            parent: Some(self),
+        }
+    }
+
+    fn ancestor(&self, levels: u32) -> Option<&Scope<'s>> {
+        let mut scope = self;
+        for _ in 0..levels {
+            scope = scope.parent?;
+        }
+        Some(scope)
+    }
+}
+
+struct Evaluator<'a> {
+    params: &'a BTreeMap<String, Value>,
+    dataset: &'a Dataset,
+}
+
+impl Evaluator<'_> {
+    fn eval(&self, node: &Node, scope: &Scope) -> Result<Value, EvalError> {
+        Ok(match &node.kind {
+            NodeKind::This => scope.value.clone(),
+            NodeKind::Parent { levels } => scope
+                .ancestor(*levels)
+                .map_or(Value::Null, |s| s.value.clone()),
+            NodeKind::Parameter { name } => self
+                .params
+                .get(name)
+                .cloned()
+                .ok_or_else(|| error(node, format!("Param ${} is not defined", name)))?,
+            NodeKind::Value { value } => value.clone(),
+            NodeKind::AccessAttribute { base: None, name } => attribute(scope.value, name),
+            NodeKind::AccessAttribute { base: Some(_), .. }
+            | NodeKind::AccessElement { .. }
+            | NodeKind::Slice { .. }
+            | NodeKind::Filter { .. }
+            | NodeKind::ArrayCoerce { .. }
+            | NodeKind::Deref { .. }
+            | NodeKind::Projection { .. }
+            | NodeKind::Everything
+            | NodeKind::Array { .. } => self.traverse(node, scope)?.into_value(),
+            NodeKind::PipeFuncCall { name, .. } if name == "order" => {
+                self.traverse(node, scope)?.into_value()
+            }
+            NodeKind::PipeFuncCall { name, .. } => {
+                return Err(unsupported(node, name));
+            }
+            NodeKind::FuncCall {
+                namespace,
+                name,
+                args,
+            } => self.function(node, namespace, name, args, scope)?,
+            NodeKind::Object { attributes } => self.object(attributes, scope)?,
+            NodeKind::OpCall {
+                op: OpKind::In,
+                left,
+                right,
+            } => match &right.kind {
+                NodeKind::Range {
+                    left: low,
+                    right: high,
+                    inclusive,
+                } => {
+                    let value = self.eval(left, scope)?;
+                    let low = self.eval(low, scope)?;
+                    let high = self.eval(high, scope)?;
+                    match (compare(&low, &value), compare(&value, &high)) {
+                        (Some(lower), Some(upper)) => Value::Bool(
+                            lower != Ordering::Greater
+                                && (upper == Ordering::Less
+                                    || *inclusive && upper == Ordering::Equal),
+                        ),
+                        _ => Value::Null,
+                    }
+                }
+                _ => operator(
+                    OpKind::In,
+                    &self.eval(left, scope)?,
+                    &self.eval(right, scope)?,
+                ),
+            },
+            NodeKind::OpCall { op, left, right } => {
+                operator(*op, &self.eval(left, scope)?, &self.eval(right, scope)?)
+            }
+            NodeKind::And { left, right } => match self.eval(left, scope)? {
+                Value::Bool(false) => Value::Bool(false),
+                left => match (left, self.eval(right, scope)?) {
+                    (_, Value::Bool(false)) => Value::Bool(false),
+                    (Value::Bool(true), Value::Bool(true)) => Value::Bool(true),
+                    _ => Value::Null,
+                },
+            },
+            NodeKind::Or { left, right } => match self.eval(left, scope)? {
+                Value::Bool(true) => Value::Bool(true),
+                left => match (left, self.eval(right, scope)?) {
+                    (_, Value::Bool(true)) => Value::Bool(true),
+                    (Value::Bool(false), Value::Bool(false)) => Value::Bool(false),
+                    _ => Value::Null,
+                },
+            },
+            NodeKind::Not { base } => match self.eval(base, scope)? {
+                Value::Bool(b) => Value::Bool(!b),
+                _ => Value::Null,
+            },
+            NodeKind::Neg { base } => match self.eval(base, scope)?.as_f64() {
+                Some(n) => number(-n),
+                None => Value::Null,
+            },
+            NodeKind::Pos { base } => match self.eval(base, scope)? {
+                n @ Value::Number(_) => n,
+                _ => Value::Null,
+            },
+            NodeKind::Group { base } => self.eval(base, scope)?,
+            NodeKind::Range { .. } => {
+                return Err(error(node, "Ranges can only be used with `in`"));
+            }
+            NodeKind::Pair { .. } => {
+                return Err(error(node, "`=>` can only be used in select()"));
+            }
+            NodeKind::Asc { .. } | NodeKind::Desc { .. } => {
+                return Err(error(node, "asc and desc can only be used in order()"));
+            }
+        })
+    }
+
+    /// Evaluate a traversal, keeping track of whether later traversals
+    /// apply to the array as a whole or to each of its elements
+    fn traverse(&self, node: &Node, scope: &Scope) -> Result<Traversal, EvalError> {
+        Ok(match &node.kind {
+            NodeKind::Everything => Traversal::Array(self.dataset.documents().to_vec()),
+            NodeKind::Array { elements } => {
+                let mut items = Vec::new();
+                for element in elements {
+                    match self.eval(&element.value, scope)? {
+                        Value::Array(spread) if element.splat => items.extend(spread),
+                        value => items.push(value),
+                    }
+                }
+                Traversal::Array(items)
+            }
+            NodeKind::PipeFuncCall { base, args, .. } => match self.eval(base, scope)? {
+                Value::Array(items) => Traversal::Array(self.order(items, args, scope)?),
+                _ => Traversal::Value(Value::Null),
+            },
+            NodeKind::AccessAttribute {
+                base: Some(base),
+                name,
+            } => self
+                .traverse(base, scope)?
+                .map(|value| Ok(attribute(&value, name)))?,
+            NodeKind::AccessElement { base, index } => {
+                let base = self.traverse(base, scope)?;
+                match self.eval(index, scope)? {
+                    Value::String(name) => base.map(|value| Ok(attribute(&value, &name)))?,
+                    index => base.element(|value| Ok(element(&value, &index)))?,
+                }
+            }
+            NodeKind::Deref { base } => self
+                .traverse(base, scope)?
+                .map(|value| Ok(self.deref(&value)))?,
+            NodeKind::Projection { base, expr } => match self.traverse(base, scope)? {
+                Traversal::Value(Value::Array(items)) | Traversal::Array(items) => {
+                    Traversal::Array(
+                        items
+                            .iter()
+                            .map(|item| self.project(item, expr, scope))
+                            .collect::<Result<_, _>>()?,
+                    )
+                }
+                base => base.map(|value| match value {
+                    Value::Object(_) => self.project(&value, expr, scope),
+                    _ => Ok(Value::Null),
+                })?,
+            },
+            NodeKind::Slice {
+                base,
+                left,
+                right,
+                inclusive,
+            } => {
+                let base = self.traverse(base, scope)?;
+                let left = self.integer(left, scope)?;
+                let right = self.integer(right, scope)?;
+                base.array(|items| Ok(slice(&items, left, right, *inclusive)))?
+            }
+            NodeKind::Filter { base, expr } => {
+                // `*[...]` is by far the most common filter, so documents
+                // are only copied once they match
+                if matches!(base.kind, NodeKind::Everything) {
+                    Traversal::Array(self.keep(self.dataset.documents(), expr, scope)?)
+                } else {
+                    self.traverse(base, scope)?
+                        .array(|items| self.keep(&items, expr, scope))?
+                }
+            }
+            NodeKind::ArrayCoerce { base } => self.traverse(base, scope)?.array(Ok)?,
+            _ => Traversal::Value(self.eval(node, scope)?),
+        })
+    }
+
+    fn integer(&self, node: &Node, scope: &Scope) -> Result<i64, EvalError> {
+        self.eval(node, scope)?
+            .as_i64()
+            .ok_or_else(|| error(node, "Slice bounds must be integers"))
+    }
+
+    fn keep(&self, items: &[Value], expr: &Node, scope: &Scope) -> Result<Vec<Value>, EvalError> {
+        let mut kept = Vec::new();
+        for item in items {
+            if self.eval(expr, &scope.nested(item))? == Value::Bool(true) {
+                kept.push(item.clone());
+            }
+        }
+        Ok(kept)
+    }
+
+    fn deref(&self, value: &Value) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.deref(v)).collect()),
+            Value::Object(object) => object
+                .get("_ref")
+                .and_then(Value::as_str)
+                .and_then(|id| self.dataset.get(id))
+                .cloned()
+                .unwrap_or(Value::Null),
+            _ => Value::Null,
+        }
+    }
+
+    fn project(&self, value: &Value, expr: &Node, scope: &Scope) -> Result<Value, EvalError> {
+        self.eval(expr, &scope.nested(value))
+    }
+
+    fn object(&self, attributes: &[ObjectAttribute], scope: &Scope) -> Result<Value, EvalError> {
+        let mut object = Map::new();
+        for attribute in attributes {
+            match attribute {
+                ObjectAttribute::Value { key, value, .. } => {
+                    let Some(name) = key.clone().or_else(|| implicit_name(value)) else {
+                        return Err(error(value, "This attribute needs a key"));
+                    };
+                    object.insert(name, self.eval(value, scope)?);
+                }
+                ObjectAttribute::Splat { value, .. } => {
+                    let value = match value {
+                        Some(value) => self.eval(value, scope)?,
+                        None => scope.value.clone(),
+                    };
+                    if let Value::Object(fields) = value {
+                        object.extend(fields);
+                    }
+                }
+                ObjectAttribute::Conditional {
+                    condition, value, ..
+                } => {
+                    if self.eval(condition, scope)? == Value::Bool(true) {
+                        if let Value::Object(fields) = self.eval(value, scope)? {
+                            object.extend(fields);
+                        }
+                    }
+                }
+            }
+        }
+        // Content Lake leaves out null attributes, which `infer` models as
+        // optional keys
+        object.retain(|_, value| !value.is_null());
+        Ok(Value::Object(object))
+    }
+
+    fn order(
+        &self,
+        items: Vec<Value>,
+        args: &[Node],
+        scope: &Scope,
+    ) -> Result<Vec<Value>, EvalError> {
+        let mut keyed = Vec::with_capacity(items.len());
+        for item in items {
+            let mut keys = Vec::with_capacity(args.len());
+            for arg in args {
+                let (expr, descending) = match &arg.kind {
+                    NodeKind::Asc { base } => (&**base, false),
+                    NodeKind::Desc { base } => (&**base, true),
+                    _ => (arg, false),
+                };
+                keys.push((self.eval(expr, &scope.nested(&item))?, descending));
+            }
+            keyed.push((keys, item));
+        }
+
+        keyed.sort_by(|(a, _), (b, _)| {
+            a.iter()
+                .zip(b)
+                .map(|((a, descending), (b, _))| {
+                    let ordering = sort_order(a, b);
+                    if *descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+        Ok(keyed.into_iter().map(|(_, item)| item).collect())
+    }
+
+    fn function(
+        &self,
+        node: &Node,
+        namespace: &str,
+        name: &str,
+        args: &[Node],
+        scope: &Scope,
+    ) -> Result<Value, EvalError> {
+        // Lazily evaluated functions first
+        match (namespace, name) {
+            ("global", "coalesce") => {
+                for arg in args {
+                    let value = self.eval(arg, scope)?;
+                    if !value.is_null() {
+                        return Ok(value);
+                    }
+                }
+                return Ok(Value::Null);
+            }
+            ("global", "select") => {
+                for arg in args {
+                    match &arg.kind {
+                        NodeKind::Pair { left, right } => {
+                            if self.eval(left, scope)? == Value::Bool(true) {
+                                return self.eval(right, scope);
+                            }
+                        }
+                        _ => return self.eval(arg, scope),
+                    }
+                }
+                return Ok(Value::Null);
+            }
+            _ => {}
+        }
+
+        let args: Vec<Value> = args
+            .iter()
+            .map(|arg| self.eval(arg, scope))
+            .collect::<Result<_, _>>()?;
+        let arg = |i: usize| args.get(i).unwrap_or(&Value::Null);
+
+        Ok(match (namespace, name) {
+            ("global", "count") => match arg(0) {
+                Value::Array(items) => Value::from(items.len()),
+                _ => Value::Null,
+            },
+            ("global", "length") => match arg(0) {
+                Value::Array(items) => Value::from(items.len()),
+                Value::String(s) => Value::from(s.chars().count()),
+                _ => Value::Null,
+            },
+            ("global", "defined") => Value::Bool(!arg(0).is_null()),
+            ("global", "round") => match (arg(0).as_f64(), arg(1)) {
+                (Some(n), Value::Null) => number(n.round()),
+                (Some(n), precision) => match precision.as_i64() {
+                    Some(p) if p >= 0 => {
+                        let scale = 10f64.powi(p as i32);
+                        number((n * scale).round() / scale)
+                    }
+                    _ => Value::Null,
+                },
+                _ => Value::Null,
+            },
+            ("global", "string") => match arg(0) {
+                s @ Value::String(_) => s.clone(),
+                v @ (Value::Number(_) | Value::Bool(_)) => Value::String(v.to_string()),
+                _ => Value::Null,
+            },
+            ("global", "lower") => map_str(arg(0), |s| Value::String(s.to_lowercase())),
+            ("global", "upper") => map_str(arg(0), |s| Value::String(s.to_uppercase())),
+            ("global", "references") => {
+                let ids: Vec<&str> = args
+                    .iter()
+                    .flat_map(|arg| match arg {
+                        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+                        other => other.as_str().into_iter().collect::<Vec<_>>(),
+                    })
+                    .collect();
+                Value::Bool(has_reference(scope.value, &ids))
+            }
+            ("array", "join") => match (arg(0), arg(1)) {
+                (Value::Array(items), Value::String(separator)) => {
+                    let parts: Option<Vec<String>> = items
+                        .iter()
+                        .map(|item| match item {
+                            Value::String(s) => Some(s.clone()),
+                            Value::Number(_) | Value::Bool(_) => Some(item.to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    parts.map_or(Value::Null, |parts| Value::String(parts.join(separator)))
+                }
+                _ => Value::Null,
+            },
+            ("array", "compact") => match arg(0) {
+                Value::Array(items) => {
+                    Value::Array(items.iter().filter(|v| !v.is_null()).cloned().collect())
+                }
+                _ => Value::Null,
+            },
+            ("array", "unique") => match arg(0) {
+                Value::Array(items) => {
+                    let mut unique: Vec<Value> = Vec::new();
+                    for item in items {
+                        let primitive = !matches!(item, Value::Array(_) | Value::Object(_));
+                        if !primitive || !unique.iter().any(|u| equal(u, item)) {
+                            unique.push(item.clone());
+                        }
+                    }
+                    Value::Array(unique)
+                }
+                _ => Value::Null,
+            },
+            ("math", "sum" | "avg" | "min" | "max") => math(name, arg(0)),
+            ("string", "startsWith") => match (arg(0), arg(1)) {
+                (Value::String(s), Value::String(prefix)) => {
+                    Value::Bool(s.starts_with(prefix.as_str()))
+                }
+                _ => Value::Null,
+            },
+            ("string", "split") => match (arg(0), arg(1)) {
+                (Value::String(s), Value::String(separator)) => Value::Array(
+                    s.split(separator.as_str())
+                        .map(|part| Value::String(part.to_string()))
+                        .collect(),
+                ),
+                _ => Value::Null,
+            },
+            ("pt", "text") => portable_text(arg(0)),
+            ("global", _) => return Err(unsupported(node, name)),
+            _ => return Err(unsupported(node, &format!("{}::{}", namespace, name))),
+        })
+    }
+}
+
+fn error(node: &Node, message: impl Into<String>) -> EvalError {
+    EvalError {
+        message: message.into(),
+        span: node.span(),
+    }
+}
+
+fn unsupported(node: &Node, name: &str) -> EvalError {
+    error(
+        node,
+        format!("{}() is not supported by the evaluator", name),
+    )
+}
+
+/// The value of a traversal, and how the traversals after it apply
+enum Traversal {
+    /// A single value, which later traversals apply to
+    Value(Value),
+    /// An array from `*`, a filter, a slice or `[]`. Attribute access, `->`
+    /// and element access on it start applying to each element.
+    Array(Vec<Value>),
+    /// The results of applying traversals to each element of an array.
+    /// Array traversals (filters, slices, `[]`) also apply to each element,
+    /// and their results are flattened: `*[...].tags[]` is every tag.
+    Each(Vec<Value>),
+}
+
+impl Traversal {
+    fn into_value(self) -> Value {
+        match self {
+            Traversal::Value(value) => value,
+            Traversal::Array(items) | Traversal::Each(items) => Value::Array(items),
+        }
+    }
+
+    /// Apply attribute access or `->`: to each element of an array
+    /// traversal, and to a single value as-is
+    fn map(
+        self,
+        mut f: impl FnMut(Value) -> Result<Value, EvalError>,
+    ) -> Result<Traversal, EvalError> {
+        Ok(match self {
+            Traversal::Value(value) => Traversal::Value(f(value)?),
+            Traversal::Array(items) | Traversal::Each(items) => {
+                Traversal::Each(items.into_iter().map(f).collect::<Result<_, _>>()?)
+            }
+        })
+    }
+
+    /// Apply element access, which picks from an array traversal as a
+    /// whole
+    fn element(
+        self,
+        mut f: impl FnMut(Value) -> Result<Value, EvalError>,
+    ) -> Result<Traversal, EvalError> {
+        Ok(match self {
+            Traversal::Value(value) => Traversal::Value(f(value)?),
+            Traversal::Array(items) => Traversal::Value(f(Value::Array(items))?),
+            Traversal::Each(items) => {
+                Traversal::Each(items.into_iter().map(f).collect::<Result<_, _>>()?)
+            }
+        })
+    }
+
+    /// Apply an array traversal. Anything but an array becomes `null`.
+    fn array(
+        self,
+        mut f: impl FnMut(Vec<Value>) -> Result<Vec<Value>, EvalError>,
+    ) -> Result<Traversal, EvalError> {
+        Ok(match self {
+            Traversal::Value(Value::Array(items)) | Traversal::Array(items) => {
+                Traversal::Array(f(items)?)
+            }
+            Traversal::Value(_) => Traversal::Value(Value::Null),
+            Traversal::Each(items) => {
+                let mut flattened = Vec::new();
+                for item in items {
+                    if let Value::Array(item) = item {
+                        flattened.extend(f(item)?);
+                    }
+                }
+                Traversal::Array(flattened)
+            }
+        })
+    }
+}
+
+/// Read an attribute of an object; anything else, arrays included, has none
+fn attribute(value: &Value, name: &str) -> Value {
+    match value {
+        Value::Object(object) => object.get(name).cloned().unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+fn element(value: &Value, index: &Value) -> Value {
+    match (value, index.as_i64()) {
+        (Value::Array(items), Some(i)) => {
+            resolve_index(i, items.len()).map_or(Value::Null, |i| items[i].clone())
+        }
+        _ => Value::Null,
+    }
+}
+
+/// The items from `left` to `right`, including `right` if `inclusive` (`..`
+/// rather than `...`). Negative bounds count from the end.
+fn slice(items: &[Value], left: i64, right: i64, inclusive: bool) -> Vec<Value> {
+    let len = items.len() as i64;
+    let resolve = |i: i64| if i < 0 { len + i } else { i };
+    let start = resolve(left).clamp(0, len) as usize;
+    let end = (resolve(right) + i64::from(inclusive)).clamp(0, len) as usize;
+    items[start..end.max(start)].to_vec()
+}
+
+/// Index into an array of `len` items, counting from the end when negative
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+/// A number value, kept integral when it is a whole number
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn map_str(value: &Value, f: impl Fn(&str) -> Value) -> Value {
+    value.as_str().map_or(Value::Null, f)
+}
+
+/// `==`, where `1` and `1.0` are equal
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+/// Order of two numbers or two strings; other pairs don't compare
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Total order used by `order()`: values of different types sort by type
+/// (numbers, strings, booleans, then anything else), with `null` last
+fn sort_order(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Number(_) => 0,
+            Value::String(_) => 1,
+            Value::Bool(_) => 2,
+            Value::Array(_) | Value::Object(_) => 3,
+            Value::Null => 4,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => compare(a, b).unwrap_or_else(|| rank(a).cmp(&rank(b))),
+    }
+}
+
+fn operator(op: OpKind, left: &Value, right: &Value) -> Value {
+    let ordered = |accept: fn(Ordering) -> bool| {
+        compare(left, right).map_or(Value::Null, |o| Value::Bool(accept(o)))
+    };
+    let arithmetic = |f: fn(f64, f64) -> f64| match (left.as_f64(), right.as_f64()) {
+        (Some(a), Some(b)) => number(f(a, b)),
+        _ => Value::Null,
+    };
+
+    match op {
+        OpKind::Eq => Value::Bool(equal(left, right)),
+        OpKind::NotEq => Value::Bool(!equal(left, right)),
+        OpKind::Lt => ordered(|o| o == Ordering::Less),
+        OpKind::LtEq => ordered(|o| o != Ordering::Greater),
+        OpKind::Gt => ordered(|o| o == Ordering::Greater),
+        OpKind::GtEq => ordered(|o| o != Ordering::Less),
+        OpKind::In => match right {
+            Value::Array(items) => Value::Bool(items.iter().any(|item| equal(left, item))),
+            _ => Value::Null,
+        },
+        OpKind::Match => text_match(left, right),
+        OpKind::Add => match (left, right) {
+            (Value::String(a), Value::String(b)) => Value::String(format!("{}{}", a, b)),
+            (Value::Array(a), Value::Array(b)) => Value::Array([a.clone(), b.clone()].concat()),
+            (Value::Object(a), Value::Object(b)) => {
+                let mut merged = a.clone();
+                merged.extend(b.clone());
+                Value::Object(merged)
+            }
+            _ => arithmetic(|a, b| a + b),
+        },
+        OpKind::Sub => arithmetic(|a, b| a - b),
+        OpKind::Mul => arithmetic(|a, b| a * b),
+        OpKind::Div if right.as_f64() == Some(0.0) => Value::Null,
+        OpKind::Div => arithmetic(|a, b| a / b),
+        OpKind::Mod if right.as_f64() == Some(0.0) => Value::Null,
+        OpKind::Mod => arithmetic(|a, b| a % b),
+        OpKind::Pow => arithmetic(f64::powf),
+    }
+}
+
+/// `text match pattern`: every term of the pattern must match a word of
+/// the text, case-insensitively, with `*` matching any characters
+fn text_match(text: &Value, pattern: &Value) -> Value {
+    fn strings(value: &Value) -> Option<Vec<&str>> {
+        match value {
+            Value::String(s) => Some(vec![s]),
+            Value::Array(items) => items.iter().map(Value::as_str).collect(),
+            _ => None,
+        }
+    }
+    fn words(s: &str, keep: char) -> Vec<String> {
+        s.split(|c: char| !c.is_alphanumeric() && c != keep)
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    let (Some(text), Some(pattern)) = (strings(text), strings(pattern)) else {
+        return Value::Bool(false);
+    };
+    let text: Vec<String> = text.iter().flat_map(|t| words(t, '\0')).collect();
+    let terms: Vec<String> = pattern.iter().flat_map(|p| words(p, '*')).collect();
+    Value::Bool(
+        !terms.is_empty()
+            && terms
+                .iter()
+                .all(|term| text.iter().any(|word| glob(term, word))),
+    )
+}
+
+/// Whether `word` matches `pattern`, where `*` matches any characters
+fn glob(pattern: &str, word: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let [first, middle @ .., last] = parts.as_slice() else {
+        return pattern == word;
+    };
+    let Some(mut rest) = word.strip_prefix(first) else {
+        return false;
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+fn has_reference(value: &Value, ids: &[&str]) -> bool {
+    match value {
+        Value::Object(object) => {
+            object
+                .get("_ref")
+                .and_then(Value::as_str)
+                .is_some_and(|id| ids.contains(&id))
+                || object.values().any(|v| has_reference(v, ids))
+        }
+        Value::Array(items) => items.iter().any(|v| has_reference(v, ids)),
+        _ => false,
+    }
+}
+
+fn math(name: &str, value: &Value) -> Value {
+    let Value::Array(items) = value else {
+        return Value::Null;
+    };
+    // Nulls are skipped; any other non-number makes the result null
+    let numbers: Option<Vec<f64>> = items
+        .iter()
+        .filter(|v| !v.is_null())
+        .map(Value::as_f64)
+        .collect();
+    let Some(numbers) = numbers else {
+        return Value::Null;
+    };
+
+    match name {
+        "sum" => number(numbers.iter().sum()),
+        _ if numbers.is_empty() => Value::Null,
+        "avg" => number(numbers.iter().sum::<f64>() / numbers.len() as f64),
+        "min" => number(numbers.iter().copied().fold(f64::INFINITY, f64::min)),
+        _ => number(numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+    }
+}
+
+/// Plain text of Portable Text blocks, one paragraph per block
+fn portable_text(value: &Value) -> Value {
+    let blocks = match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![value],
+        _ => return Value::Null,
+    };
+    let paragraphs: Vec<String> = blocks
+        .into_iter()
+        .filter(|block| block["_type"] == "block")
+        .map(|block| {
+            block["children"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|child| child["text"].as_str())
+                .collect()
+        })
+        .collect();
+    Value::String(paragraphs.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+    use serde_json::json;
+
+    const DATASET: &str = r#"
+{"_id": "p1", "_type": "post", "title": "Hello world", "views": 10, "author": {"_ref": "a1"}, "authors": [{"_ref": "a1"}, {"_ref": "a2"}], "tags": ["a", "b"]}
+{"_id": "p2", "_type": "post", "title": "Second post", "views": 30, "author": {"_ref": "a2"}, "tags": ["c"]}
+{"_id": "p3", "_type": "post", "title": "Draft", "views": 20, "author": {"_ref": "missing"}}
+{"_id": "a1", "_type": "author", "name": "Ada"}
+{"_id": "a2", "_type": "author", "name": "Grace"}
+"#;
+
+    fn run(query: &str, params: Value) -> Result<Value, EvalError> {
+        let params: BTreeMap<String, Value> = serde_json::from_value(params).unwrap();
+        let dataset = Dataset::from_ndjson(DATASET).unwrap();
+        evaluate(&parse(query).unwrap(), &params, &dataset)
+    }
+
+    #[test]
+    fn test_evaluate_query() {
+        let result = run(
+            r#"*[_type == "post" && views >= $min] | order(views desc) { title, "author": author->name, "tagCount": count(tags) }[0...2]"#,
+            json!({ "min": 15 }),
+        );
+        assert_eq!(
+            result.unwrap(),
+            json!([
+                { "title": "Second post", "author": "Grace", "tagCount": 1 },
+                { "title": "Draft" }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_evaluate_functions_and_operators() {
+        let result = run(
+            r#"{
+                "first": *[_type == "author"][0]._id,
+                "matched": *[title match "hell*"]._id,
+                "cited": *[_type == "author" && count(*[references(^._id)]) > 0].name,
+                "label": select(count(*) > 3 => "many", "few"),
+                "sum": math::sum(*[_type == "post"].views),
+                "inRange": 5 in 1..5,
+                "text": pt::text([{"_type": "block", "children": [{"text": "Hi"}, {"text": "!"}]}])
+            }"#,
+            json!({}),
+        );
+        assert_eq!(
+            result.unwrap(),
+            json!({
+                "first": "a1",
+                "matched": ["p1"],
+                "cited": ["Ada", "Grace"],
+                "label": "many",
+                "sum": 60,
+                "inRange": true,
+                "text": "Hi!"
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_traversals() {
+        let result = run(
+            r#"{
+                "all": [1, 2, 3][0..-1],
+                "allButLast": [1, 2, 3][0...-1],
+                "last": [1, 2, 3][-1..-1],
+                "tags": *[_type == "post"].tags,
+                "flat": *[_type == "post"].tags[],
+                "firstTags": *[_type == "post"].tags[0],
+                "names": *[_id == "p1"][0]{ "all": authors[]._ref, "none": authors._ref }
+            }"#,
+            json!({}),
+        );
+        assert_eq!(
+            result.unwrap(),
+            json!({
+                "all": [1, 2, 3],
+                "allButLast": [1, 2],
+                "last": [3],
+                "tags": [["a", "b"], ["c"], null],
+                "flat": ["a", "b", "c"],
+                "firstTags": ["a", "c", null],
+                "names": { "all": ["a1", "a2"] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let error = run("*[slug == $slug]", json!({})).unwrap_err();
+        assert_eq!(error.message, "Param $slug is not defined");
+        assert_eq!(error.span, Span::new(10, 15));
+
+        let error = run("*[_type == \"post\"] | score(title match \"x\")", json!({})).unwrap_err();
+        assert_eq!(error.message, "score() is not supported by the evaluator");
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("hell*", "hello"));
+        assert!(glob("*orld", "world"));
+        assert!(!glob("he*lo*x", "hello"));
+        assert!(glob("word", "word"));
+    }
+}
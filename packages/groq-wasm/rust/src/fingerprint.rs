@@ -0,0 +1,144 @@
+//! Normalized form of a query for fingerprinting.
+//!
+//! The fingerprint hashes the parse tree without spans or `Group` nodes, so
+//! whitespace, comments and redundant parentheses never change it. The tree
+//! already encodes precedence, so dropping parentheses can't make different
+//! queries match. Two options widen what counts as the same
+//! query:
+//!
+//! ```json
+//! { "ignoreFilterOrder": true, "literalPlaceholders": true }
+//! ```
+//!
+//! `ignoreFilterOrder` treats the operands of `&&` and `||` as unordered,
+//! so `_type == "post" && defined(slug)` matches its reverse.
+//! `literalPlaceholders` replaces every string, number, boolean and null
+//! literal by a placeholder, so queries that only differ in values match.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::ast::Node;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FingerprintOptions {
+    #[serde(default)]
+    pub ignore_filter_order: bool,
+    #[serde(default)]
+    pub literal_placeholders: bool,
+}
+
+impl FingerprintOptions {
+    /// Parse options from their JSON form. `None` yields the defaults.
+    pub fn from_json(json: Option<&str>) -> Result<Self, String> {
+        match json {
+            Some(json) => serde_json::from_str(json).map_err(|e| e.to_string()),
+            None => Ok(FingerprintOptions::default()),
+        }
+    }
+}
+
+/// Canonical JSON text of a parse tree under `options`
+pub fn normalize(root: &Node, options: &FingerprintOptions) -> String {
+    let mut value = serde_json::to_value(root).unwrap_or(Value::Null);
+    normalize_value(&mut value, options);
+    // Object keys serialize sorted, so equal trees give equal text
+    value.to_string()
+}
+
+fn normalize_value(value: &mut Value, options: &FingerprintOptions) {
+    match value {
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| normalize_value(item, options)),
+        Value::Object(map) => {
+            map.remove("span");
+            map.values_mut()
+                .for_each(|child| normalize_value(child, options));
+
+            let kind = map.get("type").and_then(Value::as_str).unwrap_or_default();
+            if kind == "Group" {
+                if let Some(base) = map.remove("base") {
+                    *value = base;
+                }
+            } else if options.literal_placeholders && kind == "Value" {
+                map.remove("value");
+            } else if options.ignore_filter_order && (kind == "And" || kind == "Or") {
+                let kind = kind.to_string();
+                let mut operands = Vec::new();
+                flatten(&kind, std::mem::take(map), &mut operands);
+                operands.sort_by_cached_key(Value::to_string);
+
+                map.insert("type".to_string(), Value::String(kind));
+                map.insert("operands".to_string(), Value::Array(operands));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect the operands of a chain of `kind` nodes. Children were already
+/// normalized, so nested chains are flat operand lists by now.
+fn flatten(kind: &str, mut node: Map<String, Value>, operands: &mut Vec<Value>) {
+    let is_chain =
+        |node: &Map<String, Value>| node.get("type").and_then(Value::as_str) == Some(kind);
+
+    if !is_chain(&node) {
+        operands.push(Value::Object(node));
+        return;
+    }
+    if let Some(Value::Array(items)) = node.remove("operands") {
+        operands.extend(items);
+        return;
+    }
+    for side in ["left", "right"] {
+        match node.remove(side) {
+            Some(Value::Object(child)) => flatten(kind, child, operands),
+            Some(other) => operands.push(other),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    fn normalized(query: &str, options: &str) -> String {
+        let options = FingerprintOptions::from_json(Some(options)).unwrap();
+        normalize(&parse(query).unwrap(), &options)
+    }
+
+    #[test]
+    fn test_normalize() {
+        let query = "*[_type == \"post\" && slug.current == \"a\" && defined(title)]{title}";
+        let reordered =
+            "*[defined(title) && _type == \"post\"\n  && slug.current == \"b\"] { title }";
+
+        assert_ne!(normalized(query, "{}"), normalized(reordered, "{}"));
+        assert_ne!(
+            normalized(query, r#"{"ignoreFilterOrder": true}"#),
+            normalized(reordered, r#"{"ignoreFilterOrder": true}"#)
+        );
+        let both = r#"{"ignoreFilterOrder": true, "literalPlaceholders": true}"#;
+        assert_eq!(normalized(query, both), normalized(reordered, both));
+
+        // Parentheses only matter through the tree they produce
+        assert_eq!(
+            normalized("*[(_type == \"post\")]{title}", "{}"),
+            normalized("*[_type == \"post\"]{title}", "{}")
+        );
+        assert_eq!(
+            normalized("*[((a || b)) && c]", both),
+            normalized("*[c && (a || b)]", both)
+        );
+
+        // `||` operands don't mix with the `&&` around them
+        assert_ne!(
+            normalized("*[a || b && c]", both),
+            normalized("*[(a || b) && c]", both)
+        );
+    }
+}
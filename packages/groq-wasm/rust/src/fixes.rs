@@ -0,0 +1,214 @@
+//! Help text and machine-applicable fixes for groq-lint findings.
+//!
+//! groq-lint reports where a problem is but not how to fix it. For rules
+//! whose fix is mechanical, the query is parsed here, the offending nodes
+//! are located again and each is paired with the finding that covers it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ast::{Node, NodeKind, OpKind, Walk};
+use crate::positions::Span;
+
+/// Rules that have help text, and possibly fixes, attached here, with how
+/// safe their fixes are
+const FIXABLE_RULES: &[(&str, &str, Applicability)] = &[
+    (
+        "join_to_get_id",
+        "Replace `reference->_id` with `reference._ref` for better performance.",
+        Applicability::MachineApplicable,
+    ),
+    (
+        "match_on_id",
+        "Consider using `==` for exact matches or `string::startsWith()` for prefix matching.",
+        Applicability::MaybeIncorrect,
+    ),
+];
+
+/// Whether a suggestion can be applied without a person reviewing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Applicability {
+    /// Keeps the query's results unchanged; `fix` applies it
+    MachineApplicable,
+    /// Probably what was meant, but may change results; never auto-applied
+    MaybeIncorrect,
+}
+
+/// A single text edit that resolves a finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub description: String,
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+/// A place in the query a rule applies to
+pub struct Site {
+    pub span: Span,
+    pub suggestion: Option<Suggestion>,
+}
+
+/// Whether `fix` can resolve findings of `rule_id`
+pub fn is_fixable_rule(rule_id: &str) -> bool {
+    FIXABLE_RULES.iter().any(|(id, _, applicability)| {
+        *id == rule_id && *applicability == Applicability::MachineApplicable
+    })
+}
+
+pub fn help(rule_id: &str) -> Option<&'static str> {
+    FIXABLE_RULES
+        .iter()
+        .find(|(id, _, _)| *id == rule_id)
+        .map(|(_, help, _)| *help)
+}
+
+/// Every place `rule_id` applies to, in source order
+pub fn sites(rule_id: &str, root: &Node, query: &str) -> Vec<Site> {
+    let mut sites = Vec::new();
+
+    root.walk(&mut |node| {
+        let site = match rule_id {
+            "join_to_get_id" => join_to_get_id(node),
+            "match_on_id" => match_on_id(node, query),
+            _ => None,
+        };
+        sites.extend(site);
+    });
+
+    sites.sort_by_key(|site| (site.span.start, site.span.end));
+    sites
+}
+
+/// `ref->_id` reads the referenced document only to get back `ref._ref`
+fn join_to_get_id(node: &Node) -> Option<Site> {
+    let NodeKind::AccessAttribute {
+        base: Some(deref),
+        name,
+    } = &node.kind
+    else {
+        return None;
+    };
+    let NodeKind::Deref { base } = &deref.kind else {
+        return None;
+    };
+    if name != "_id" {
+        return None;
+    }
+
+    Some(Site {
+        span: node.span(),
+        suggestion: Some(Suggestion {
+            description: "Replace `->_id` with `._ref`".to_string(),
+            span: Span::new(base.span.end, node.span.end),
+            replacement: "._ref".to_string(),
+            applicability: Applicability::MachineApplicable,
+        }),
+    })
+}
+
+/// `_id match "..."`: literal patterns become `==` or `string::startsWith()`.
+///
+/// `match` compares tokenized text, not the raw string, so the rewrite can
+/// change which documents are returned; it is only ever suggested.
+fn match_on_id(node: &Node, query: &str) -> Option<Site> {
+    let NodeKind::OpCall {
+        op: OpKind::Match,
+        left,
+        right,
+    } = &node.kind
+    else {
+        return None;
+    };
+    if !matches!(&left.kind, NodeKind::AccessAttribute { name, .. } if name == "_id") {
+        return None;
+    }
+
+    let subject = &query[left.span.start..left.span.end];
+    let suggestion = match &right.kind {
+        NodeKind::Value {
+            value: Value::String(pattern),
+        } => match pattern.find('*') {
+            None => Some(Suggestion {
+                description: "Use `==` for an exact match".to_string(),
+                span: node.span(),
+                replacement: format!("{} == {}", subject, quote(pattern)),
+                applicability: Applicability::MaybeIncorrect,
+            }),
+            Some(index) if index == pattern.len() - 1 => Some(Suggestion {
+                description: "Use `string::startsWith()` for a prefix match".to_string(),
+                span: node.span(),
+                replacement: format!(
+                    "string::startsWith({}, {})",
+                    subject,
+                    quote(&pattern[..index])
+                ),
+                applicability: Applicability::MaybeIncorrect,
+            }),
+            // Wildcards elsewhere have no exact equivalent
+            Some(_) => None,
+        },
+        _ => None,
+    };
+
+    Some(Site {
+        span: node.span(),
+        suggestion,
+    })
+}
+
+/// GROQ string literals share JSON's escape syntax
+fn quote(value: &str) -> String {
+    Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    fn apply(query: &str, rule_id: &str) -> Vec<String> {
+        let root = parse(query).unwrap();
+        sites(rule_id, &root, query)
+            .into_iter()
+            .filter_map(|site| site.suggestion)
+            .map(|s| {
+                format!(
+                    "{}{}{}",
+                    &query[..s.span.start],
+                    s.replacement,
+                    &query[s.span.end..]
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_join_to_get_id_fix() {
+        assert_eq!(
+            apply(
+                "*[_type == \"post\"]{ \"a\": author->_id, tags[]->_id }",
+                "join_to_get_id"
+            ),
+            vec![
+                "*[_type == \"post\"]{ \"a\": author._ref, tags[]->_id }",
+                "*[_type == \"post\"]{ \"a\": author->_id, tags[]._ref }",
+            ]
+        );
+        assert!(apply("*{ author->name }", "join_to_get_id").is_empty());
+    }
+
+    #[test]
+    fn test_match_on_id_fix() {
+        assert_eq!(
+            apply("*[_id match \"drafts.*\"]", "match_on_id"),
+            vec!["*[string::startsWith(_id, \"drafts.\")]"]
+        );
+        assert_eq!(
+            apply("*[_id match \"abc\"]", "match_on_id"),
+            vec!["*[_id == \"abc\"]"]
+        );
+        assert!(apply("*[_id match \"*.abc\"]", "match_on_id").is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! Formatting results as minimal edits against the input.
+//!
+//! The input and the formatted query are diffed piece by piece (see
+//! `trivia`), so an edit only covers the whitespace (or commas) the
+//! formatter actually changed.
+//! Offsets such as the cursor are carried across the same edits.
+
+use crate::diff::diff;
+use crate::positions::Span;
+use crate::trivia::{scan, Piece};
+
+/// `old` in the input is replaced by `new` in the formatted query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    pub old: Span,
+    pub new: Span,
+}
+
+/// Edits turning `input` into `formatted`, in order and non-overlapping
+pub fn edits(input: &str, formatted: &str) -> Vec<Edit> {
+    let old_pieces = scan(input);
+    let new_pieces = scan(formatted);
+    let old_text: Vec<&str> = old_pieces.iter().map(|p| p.text(input)).collect();
+    let new_text: Vec<&str> = new_pieces.iter().map(|p| p.text(formatted)).collect();
+
+    diff(&old_text, &new_text)
+        .into_iter()
+        .map(|change| Edit {
+            old: byte_span(&old_pieces, change.old, input.len()),
+            new: byte_span(&new_pieces, change.new, formatted.len()),
+        })
+        .collect()
+}
+
+/// Byte span of a run of pieces; an empty run is the point before the
+/// piece it stops at
+fn byte_span(pieces: &[Piece], range: std::ops::Range<usize>, len: usize) -> Span {
+    let start = pieces.get(range.start).map_or(len, |p| p.span.start);
+    if range.is_empty() {
+        Span::new(start, start)
+    } else {
+        Span::new(start, pieces[range.end - 1].span.end)
+    }
+}
+
+/// Where an input offset ends up after applying `edits`.
+///
+/// Offsets inside replaced text keep their distance from the start of the
+/// edit, clamped to the replacement; text inserted at an offset goes
+/// before it, so a cursor stays in front of the token it was in front of.
+pub fn map_offset(edits: &[Edit], offset: usize) -> usize {
+    // Shift of everything after the last edit passed so far
+    let mut delta: isize = 0;
+    for edit in edits {
+        if edit.old.end <= offset {
+            delta = edit.new.end as isize - edit.old.end as isize;
+        } else if edit.old.start < offset {
+            let into = (offset - edit.old.start).min(edit.new.end - edit.new.start);
+            return edit.new.start + into;
+        } else {
+            break;
+        }
+    }
+    (offset as isize + delta) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(input: &str, formatted: &str, edits: &[Edit]) -> String {
+        let mut result = input.to_string();
+        for edit in edits.iter().rev() {
+            result.replace_range(
+                edit.old.start..edit.old.end,
+                &formatted[edit.new.start..edit.new.end],
+            );
+        }
+        result
+    }
+
+    #[test]
+    fn test_edits_only_touch_whitespace() {
+        let input = "*[_type==\"post\"]{title,  body}";
+        let formatted = "*[_type == \"post\"]{ title, body }";
+        let edits = edits(input, formatted);
+        assert_eq!(apply(input, formatted, &edits), formatted);
+        assert!(edits
+            .iter()
+            .all(|e| input[e.old.start..e.old.end].trim().is_empty()));
+    }
+
+    #[test]
+    fn test_map_offset() {
+        let input = "*{title,body}";
+        let formatted = "*{ title, body }";
+        let edits = edits(input, formatted);
+
+        // Before `title`, before `body`, and the end of the input
+        assert_eq!(map_offset(&edits, 2), 3);
+        assert_eq!(map_offset(&edits, 8), 10);
+        assert_eq!(map_offset(&edits, input.len()), formatted.len());
+        // Inside `body`
+        assert_eq!(map_offset(&edits, 10), 12);
+    }
+}
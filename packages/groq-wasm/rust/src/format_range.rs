@@ -0,0 +1,101 @@
+//! Choosing what to format for a partial selection.
+//!
+//! A selection is widened to the smallest subtree that covers it and can be
+//! formatted as a query of its own. That subtree is formatted on its own and
+//! re-indented to the column it starts at, so text outside of it is left
+//! untouched.
+
+use crate::ast::{Node, NodeKind, Walk};
+use crate::positions::Span;
+use crate::trivia::{scan, PieceKind};
+
+/// Smallest node covering `selection` that is a valid query by itself
+pub fn covering_node(root: &Node, selection: Span) -> &Node {
+    let mut path = vec![root];
+    while let Some(child) = path.last().and_then(|node| child_covering(node, selection)) {
+        path.push(child);
+    }
+
+    path.into_iter()
+        .rev()
+        .find(|node| is_standalone(node))
+        .unwrap_or(root)
+}
+
+fn child_covering(node: &Node, selection: Span) -> Option<&Node> {
+    node.children()
+        .into_iter()
+        .find(|child| child.span.start <= selection.start && selection.end <= child.span.end)
+}
+
+/// Pairs and sort directions only parse inside `select()`, objects and
+/// `order()`
+fn is_standalone(node: &Node) -> bool {
+    !matches!(
+        node.kind,
+        NodeKind::Pair { .. } | NodeKind::Asc { .. } | NodeKind::Desc { .. }
+    )
+}
+
+/// Leading whitespace of the line `offset` is on, and the width of the
+/// text between it and `offset`
+pub fn line_indent(source: &str, offset: usize) -> (&str, usize) {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..offset];
+    let indent = &line[..line.len() - line.trim_start().len()];
+    (indent, line.chars().count())
+}
+
+/// Indent every line after the first, so a subtree formatted on its own
+/// lines up with the text before it. Line breaks inside string literals
+/// are part of the value and left alone.
+pub fn indent_continuation(formatted: &str, indent: &str) -> String {
+    let newline = format!("\n{}", indent);
+    scan(formatted)
+        .into_iter()
+        .map(|piece| match piece.kind {
+            PieceKind::String => piece.text(formatted).to_string(),
+            _ => piece.text(formatted).replace('\n', &newline),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    fn covering(query: &str, selected: &str) -> String {
+        let root = parse(query).unwrap();
+        let start = query.find(selected).unwrap();
+        let node = covering_node(&root, Span::new(start, start + selected.len()));
+        query[node.span.start..node.span.end].to_string()
+    }
+
+    #[test]
+    fn test_covering_node() {
+        let query = "*[_type == \"post\"]{ title, \"a\": author->{ name } } | order(title desc)";
+        assert_eq!(covering(query, "nam"), "name");
+        assert_eq!(covering(query, "author->{"), "author->{ name }");
+        assert_eq!(
+            covering(query, "title desc"),
+            "*[_type == \"post\"]{ title, \"a\": author->{ name } } | order(title desc)"
+        );
+        assert_eq!(covering(query, "== \"post\""), "_type == \"post\"");
+    }
+
+    #[test]
+    fn test_line_indent() {
+        let query = "*{\n    a,\n    b\n}";
+        let offset = query.find('b').unwrap();
+        assert_eq!(line_indent(query, offset), ("    ", 4));
+        assert_eq!(
+            indent_continuation("{\n  x\n}", "    "),
+            "{\n      x\n    }"
+        );
+        assert_eq!(
+            indent_continuation("{\n  \"a\": \"x\ny\"\n}", "  "),
+            "{\n    \"a\": \"x\ny\"\n  }"
+        );
+    }
+}
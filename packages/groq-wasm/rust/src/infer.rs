@@ -0,0 +1,656 @@
+//! Result type inference from a query and a schema.
+//!
+//! Each node is given the type of the value it evaluates to, following
+//! GROQ's rules: a traversal over `*` yields an array of the documents its
+//! filter allows, `[0]` yields a single element or `null`, projections build
+//! objects from the fields they read, `->` resolves a reference to the
+//! documents it can point at (or `null` if it dangles), and reading a field
+//! a value doesn't have yields `null`. Anything that can't be worked out is
+//! `Unknown` rather than a guess.
+//!
+//! Like Content Lake (and `evaluate`), projections leave out keys whose
+//! value is `null`, so a projected key that can be `null` is optional
+//! instead.
+
+use serde_json::Value;
+
+use crate::ast::{ArrayElement, Node, NodeKind, ObjectAttribute, OpKind};
+use crate::schema::{Schema, TypeNode, BUILT_IN_FIELDS};
+use crate::schema_rules::filter_types;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Unknown,
+    Null,
+    Boolean,
+    Number,
+    String,
+    /// A single string, number or boolean value
+    Literal(Value),
+    Array(Box<Type>),
+    Object(Vec<Field>),
+    /// A reference object pointing at one of these document types
+    Reference(Vec<String>),
+    /// A whole document of a schema type
+    Document(String),
+    Union(Vec<Type>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+    /// The key can be missing, not just `null`
+    pub optional: bool,
+}
+
+impl Field {
+    /// The field of a projection. Projections leave out keys whose value
+    /// is `null`, so a nullable key is optional and never `null`, and a key
+    /// that is always `null` is never there at all.
+    fn projected(self) -> Option<Field> {
+        if !self.ty.is_nullable() {
+            return Some(self);
+        }
+        Some(Field {
+            ty: self.ty.non_null()?,
+            optional: true,
+            ..self
+        })
+    }
+}
+
+impl Type {
+    /// Union of `types`, flattened and without duplicates. `Unknown`
+    /// absorbs everything, primitives absorb their literals and `null`
+    /// goes last. The union of no types is the empty union, `never`.
+    pub fn union(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut members: Vec<Type> = Vec::new();
+        let mut nullable = false;
+        for ty in types {
+            let flat = match ty {
+                Type::Union(of) => of,
+                other => vec![other],
+            };
+            for member in flat {
+                match member {
+                    Type::Unknown => return Type::Unknown,
+                    Type::Null => nullable = true,
+                    member if !members.contains(&member) => members.push(member),
+                    _ => {}
+                }
+            }
+        }
+        // `string | "a"` is just `string`
+        let primitives: Vec<Type> = members
+            .iter()
+            .filter(|m| matches!(m, Type::String | Type::Number | Type::Boolean))
+            .cloned()
+            .collect();
+        members.retain(|member| match member {
+            Type::Literal(Value::String(_)) => !primitives.contains(&Type::String),
+            Type::Literal(Value::Number(_)) => !primitives.contains(&Type::Number),
+            Type::Literal(Value::Bool(_)) => !primitives.contains(&Type::Boolean),
+            _ => true,
+        });
+        if nullable {
+            members.push(Type::Null);
+        }
+
+        match members.len() {
+            0 => Type::Union(members),
+            1 => members.remove(0),
+            _ => Type::Union(members),
+        }
+    }
+
+    pub fn nullable(self) -> Type {
+        Type::union([self, Type::Null])
+    }
+
+    /// The type without `null`, or `None` if it is only `null`
+    fn non_null(&self) -> Option<Type> {
+        match self {
+            Type::Null => None,
+            Type::Union(of) => Some(Type::union(
+                of.iter().filter(|t| **t != Type::Null).cloned(),
+            )),
+            other => Some(other.clone()),
+        }
+    }
+
+    fn is_nullable(&self) -> bool {
+        match self {
+            Type::Null | Type::Unknown => true,
+            Type::Union(of) => of.contains(&Type::Null),
+            _ => false,
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(self, Type::Number) || matches!(self, Type::Literal(v) if v.is_number())
+    }
+
+    fn is_string(&self) -> bool {
+        matches!(self, Type::String) || matches!(self, Type::Literal(v) if v.is_string())
+    }
+
+    /// Names of the document types used anywhere in this type
+    pub fn documents(&self, names: &mut Vec<String>) {
+        match self {
+            Type::Document(name) if !names.contains(name) => names.push(name.clone()),
+            Type::Array(of) => of.documents(names),
+            Type::Object(fields) => fields.iter().for_each(|f| f.ty.documents(names)),
+            Type::Union(of) => of.iter().for_each(|t| t.documents(names)),
+            _ => {}
+        }
+    }
+}
+
+/// Infer the type of a query's result
+pub fn infer(root: &Node, schema: &Schema) -> Type {
+    Inference { schema }.infer(root, &[])
+}
+
+/// Fields of a document type, with the built-in fields Content Lake adds
+pub fn document_fields(schema: &Schema, name: &str) -> Vec<Field> {
+    let attributes = schema.attributes(name);
+    let mut fields: Vec<Field> = BUILT_IN_FIELDS
+        .iter()
+        .filter(|field| attributes.is_none_or(|a| !a.contains_key(**field)))
+        .map(|field| Field {
+            name: field.to_string(),
+            ty: if *field == "_type" {
+                Type::Literal(Value::String(name.to_string()))
+            } else {
+                Type::String
+            },
+            optional: false,
+        })
+        .collect();
+
+    for (field, attribute) in attributes.into_iter().flatten() {
+        fields.push(Field {
+            name: field.clone(),
+            ty: from_schema(schema, &attribute.value, &mut Vec::new()),
+            optional: attribute.optional,
+        });
+    }
+    fields
+}
+
+/// Convert a schema type node, expanding named types
+fn from_schema<'a>(schema: &'a Schema, node: &'a TypeNode, visiting: &mut Vec<&'a str>) -> Type {
+    match node {
+        TypeNode::String { value: Some(v) } => Type::Literal(Value::String(v.clone())),
+        TypeNode::String { value: None } => Type::String,
+        TypeNode::Number { value: Some(v) } => Type::Literal(Value::Number(v.clone())),
+        TypeNode::Number { value: None } => Type::Number,
+        TypeNode::Boolean { value: Some(v) } => Type::Literal(Value::Bool(*v)),
+        TypeNode::Boolean { value: None } => Type::Boolean,
+        TypeNode::Null => Type::Null,
+        TypeNode::Object {
+            dereferences_to: Some(target),
+            ..
+        } => Type::Reference(vec![target.clone()]),
+        TypeNode::Object { attributes, .. } => Type::Object(
+            attributes
+                .iter()
+                .map(|(name, attribute)| Field {
+                    name: name.clone(),
+                    ty: from_schema(schema, &attribute.value, visiting),
+                    optional: attribute.optional,
+                })
+                .collect(),
+        ),
+        TypeNode::Array { of } => Type::Array(Box::new(from_schema(schema, of, visiting))),
+        TypeNode::Union { of } => {
+            Type::union(of.iter().map(|node| from_schema(schema, node, visiting)))
+        }
+        TypeNode::Inline { name } => {
+            // Recursive named types (nested menus, ...) stop being expanded
+            if visiting.contains(&name.as_str()) {
+                return Type::Unknown;
+            }
+            let Some(node) = schema.named_type(name) else {
+                return Type::Unknown;
+            };
+            visiting.push(name);
+            let ty = from_schema(schema, node, visiting);
+            visiting.pop();
+            ty
+        }
+        TypeNode::Other => Type::Unknown,
+    }
+}
+
+struct Inference<'a> {
+    schema: &'a Schema,
+}
+
+impl Inference<'_> {
+    /// `scopes` holds `@` last, then `^`, `^.^`, ...
+    fn infer(&self, node: &Node, scopes: &[Type]) -> Type {
+        match &node.kind {
+            NodeKind::Everything => Type::Array(Box::new(self.all_documents())),
+            NodeKind::This => scopes.last().cloned().unwrap_or(Type::Unknown),
+            NodeKind::Parent { levels } => scopes
+                .len()
+                .checked_sub(*levels as usize + 1)
+                .map_or(Type::Unknown, |i| scopes[i].clone()),
+            NodeKind::Parameter { .. } => Type::Unknown,
+            NodeKind::Value { value: Value::Null } => Type::Null,
+            NodeKind::Value { value } => Type::Literal(value.clone()),
+            NodeKind::AccessAttribute { base, name } => {
+                let base = match base {
+                    Some(base) => self.infer(base, scopes),
+                    None => scopes.last().cloned().unwrap_or(Type::Unknown),
+                };
+                self.attribute(&base, name)
+            }
+            NodeKind::AccessElement { base, index } => {
+                let base = self.infer(base, scopes);
+                match &index.kind {
+                    NodeKind::Value {
+                        value: Value::String(name),
+                    } => self.attribute(&base, name),
+                    _ => element(&base).map_or_else(|| null_or_unknown(&base), Type::nullable),
+                }
+            }
+            NodeKind::Slice { base, .. }
+            | NodeKind::ArrayCoerce { base }
+            | NodeKind::Group { base }
+            | NodeKind::Asc { base }
+            | NodeKind::Desc { base } => self.infer(base, scopes),
+            NodeKind::Filter { base, expr } => {
+                let base = self.infer(base, scopes);
+                match filter_types(expr, self.schema) {
+                    Some(types) => map_array(&base, &|element| narrow(element, &types)),
+                    None => base,
+                }
+            }
+            NodeKind::Projection { base, expr } => {
+                let base = self.infer(base, scopes);
+                map_array(&base, &|element| self.project(element, expr, scopes))
+            }
+            NodeKind::Deref { base } => self.deref(&self.infer(base, scopes)),
+            NodeKind::PipeFuncCall { base, name, .. } => match name.as_str() {
+                "order" => self.infer(base, scopes),
+                "score" => map_array(&self.infer(base, scopes), &|element| self.scored(element)),
+                _ => Type::Unknown,
+            },
+            NodeKind::FuncCall {
+                namespace,
+                name,
+                args,
+            } => self.function(namespace, name, args, scopes),
+            NodeKind::Object { .. } => self.object(node, scopes),
+            NodeKind::Array { elements } => Type::Array(Box::new(Type::union(
+                elements.iter().map(|e| self.array_element(e, scopes)),
+            ))),
+            NodeKind::OpCall { op, left, right } => match op {
+                OpKind::Add => {
+                    let (left, right) = (self.infer(left, scopes), self.infer(right, scopes));
+                    if left.is_number() && right.is_number() {
+                        Type::Number
+                    } else if left.is_string() && right.is_string() {
+                        Type::String
+                    } else {
+                        Type::Unknown
+                    }
+                }
+                OpKind::Sub | OpKind::Mul | OpKind::Div | OpKind::Mod | OpKind::Pow => Type::Number,
+                _ => Type::Boolean,
+            },
+            NodeKind::And { .. } | NodeKind::Or { .. } | NodeKind::Not { .. } => Type::Boolean,
+            NodeKind::Neg { .. } | NodeKind::Pos { .. } => Type::Number,
+            NodeKind::Pair { right, .. } => self.infer(right, scopes),
+            NodeKind::Range { .. } => Type::Unknown,
+        }
+    }
+
+    fn all_documents(&self) -> Type {
+        let documents = self.schema.document_types();
+        if documents.is_empty() {
+            return Type::Unknown;
+        }
+        Type::union(
+            documents
+                .into_iter()
+                .map(|name| Type::Document(name.to_string())),
+        )
+    }
+
+    /// Type of `value.name`
+    fn attribute(&self, value: &Type, name: &str) -> Type {
+        match value {
+            Type::Unknown => Type::Unknown,
+            Type::Document(document) if !self.schema.has_document(document) => Type::Unknown,
+            Type::Document(document) => field_type(&document_fields(self.schema, document), name),
+            Type::Object(fields) => field_type(fields, name),
+            Type::Reference(_) => match name {
+                "_ref" => Type::String,
+                "_type" => Type::Literal(Value::String("reference".to_string())),
+                _ => Type::Null,
+            },
+            // `array[].name` reads the field of every element
+            Type::Array(of) => Type::Array(Box::new(self.attribute(of, name))),
+            Type::Union(of) => Type::union(of.iter().map(|t| self.attribute(t, name))),
+            _ => Type::Null,
+        }
+    }
+
+    fn deref(&self, value: &Type) -> Type {
+        match value {
+            Type::Unknown => Type::Unknown,
+            // A reference can point at a deleted document
+            Type::Reference(targets) => {
+                Type::union(targets.iter().map(|t| Type::Document(t.clone()))).nullable()
+            }
+            Type::Array(of) => Type::Array(Box::new(self.deref(of))),
+            Type::Union(of) => Type::union(of.iter().map(|t| self.deref(t))),
+            _ => Type::Null,
+        }
+    }
+
+    /// Evaluate a projection's object with `value` as `@`, once per member
+    /// of a union so each document type keeps its own fields
+    fn project(&self, value: &Type, expr: &Node, scopes: &[Type]) -> Type {
+        match value {
+            Type::Null => Type::Null,
+            Type::Union(of) => Type::union(of.iter().map(|t| self.project(t, expr, scopes))),
+            _ => {
+                let mut scopes = scopes.to_vec();
+                scopes.push(value.clone());
+                self.infer(expr, &scopes)
+            }
+        }
+    }
+
+    fn object(&self, node: &Node, scopes: &[Type]) -> Type {
+        let NodeKind::Object { attributes } = &node.kind else {
+            return Type::Unknown;
+        };
+        let scope = scopes.last().cloned().unwrap_or(Type::Unknown);
+
+        let mut fields: Vec<Field> = Vec::new();
+        for attribute in attributes {
+            let added = match attribute {
+                ObjectAttribute::Value { key, value, .. } => {
+                    let Some(name) = key.clone().or_else(|| implicit_name(value)) else {
+                        continue;
+                    };
+                    vec![Field {
+                        name,
+                        ty: self.infer(value, scopes),
+                        optional: false,
+                    }]
+                }
+                ObjectAttribute::Splat { value, .. } => {
+                    let value = match value {
+                        Some(value) => self.infer(value, scopes),
+                        None => scope.clone(),
+                    };
+                    self.fields_of(&value)
+                }
+                ObjectAttribute::Conditional { value, .. } => self
+                    .fields_of(&self.infer(value, scopes))
+                    .into_iter()
+                    .map(|field| Field {
+                        optional: true,
+                        ..field
+                    })
+                    .collect(),
+            };
+            // Later keys replace earlier ones
+            for field in added {
+                fields.retain(|f| f.name != field.name);
+                fields.push(field);
+            }
+        }
+        Type::Object(fields.into_iter().filter_map(Field::projected).collect())
+    }
+
+    /// `value` with the `_score` field `score()` adds to every document
+    fn scored(&self, value: &Type) -> Type {
+        match value {
+            Type::Document(name) if !self.schema.has_document(name) => Type::Unknown,
+            Type::Document(_) | Type::Object(_) => {
+                let mut fields = self.fields_of(value);
+                fields.retain(|f| f.name != "_score");
+                fields.push(Field {
+                    name: "_score".to_string(),
+                    ty: Type::Number,
+                    optional: false,
+                });
+                Type::Object(fields)
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn fields_of(&self, value: &Type) -> Vec<Field> {
+        match value {
+            Type::Document(name) => document_fields(self.schema, name),
+            Type::Object(fields) => fields.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn array_element(&self, element: &ArrayElement, scopes: &[Type]) -> Type {
+        let value = self.infer(&element.value, scopes);
+        if element.splat {
+            element_of(&value)
+        } else {
+            value
+        }
+    }
+
+    fn function(&self, namespace: &str, name: &str, args: &[Node], scopes: &[Type]) -> Type {
+        match (namespace, name) {
+            ("global", "count" | "length" | "round") | ("math", _) => Type::Number,
+            ("global", "defined" | "references") | ("string", "startsWith") => Type::Boolean,
+            ("global", "string" | "lower" | "upper" | "now" | "dateTime")
+            | ("pt", "text")
+            | ("array", "join") => Type::String,
+            ("array", "compact" | "unique") => args
+                .first()
+                .map_or(Type::Unknown, |arg| self.infer(arg, scopes)),
+            ("global", "coalesce") => {
+                // The first non-null argument wins, so the result is only
+                // null if every argument can be
+                let types: Vec<Type> = args.iter().map(|a| self.infer(a, scopes)).collect();
+                let nullable = types.iter().all(Type::is_nullable);
+                let mut result = Type::union(types.iter().filter_map(Type::non_null));
+                if nullable {
+                    result = result.nullable();
+                }
+                result
+            }
+            ("global", "select") => {
+                let mut has_default = false;
+                let branches: Vec<Type> = args
+                    .iter()
+                    .map(|arg| {
+                        has_default |= !matches!(arg.kind, NodeKind::Pair { .. });
+                        self.infer(arg, scopes)
+                    })
+                    .collect();
+                let result = Type::union(branches);
+                if has_default {
+                    result
+                } else {
+                    result.nullable()
+                }
+            }
+            _ => Type::Unknown,
+        }
+    }
+}
+
+fn field_type(fields: &[Field], name: &str) -> Type {
+    match fields.iter().find(|f| f.name == name) {
+        Some(field) if field.optional => field.ty.clone().nullable(),
+        Some(field) => field.ty.clone(),
+        None => Type::Null,
+    }
+}
+
+/// Element type of an array, or of the arrays in a union
+fn element(value: &Type) -> Option<Type> {
+    match value {
+        Type::Array(of) => Some((**of).clone()),
+        Type::Union(of) => {
+            let elements: Vec<Type> = of.iter().filter_map(element).collect();
+            (!elements.is_empty()).then(|| Type::union(elements))
+        }
+        _ => None,
+    }
+}
+
+/// What `...value` contributes to an array
+fn element_of(value: &Type) -> Type {
+    element(value).unwrap_or_else(|| value.clone())
+}
+
+fn null_or_unknown(value: &Type) -> Type {
+    if *value == Type::Unknown {
+        Type::Unknown
+    } else {
+        Type::Null
+    }
+}
+
+/// Apply `f` to the elements of the arrays in `value`; other values are
+/// passed to `f` as they are
+fn map_array(value: &Type, f: &dyn Fn(&Type) -> Type) -> Type {
+    match value {
+        Type::Array(of) => Type::Array(Box::new(f(of))),
+        Type::Union(of) => Type::union(of.iter().map(|t| map_array(t, f))),
+        Type::Null => Type::Null,
+        other => f(other),
+    }
+}
+
+/// Keep the document types a `_type` filter allows
+fn narrow(value: &Type, types: &std::collections::BTreeSet<String>) -> Type {
+    match value {
+        Type::Union(of) => Type::union(of.iter().map(|t| narrow(t, types))),
+        Type::Document(name) if !types.contains(name) => Type::union([]),
+        other => other.clone(),
+    }
+}
+
+/// Key a projection uses for an attribute without one: `author->{name}`
+/// is keyed `author`
+pub fn implicit_name(value: &Node) -> Option<String> {
+    match &value.kind {
+        NodeKind::AccessAttribute { name, .. } => Some(name.clone()),
+        NodeKind::Deref { base }
+        | NodeKind::ArrayCoerce { base }
+        | NodeKind::Projection { base, .. }
+        | NodeKind::Filter { base, .. }
+        | NodeKind::Slice { base, .. }
+        | NodeKind::AccessElement { base, .. } => implicit_name(base),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    const SCHEMA: &str = r#"[
+        {"type": "document", "name": "post", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}},
+            "views": {"type": "objectAttribute", "value": {"type": "number"}, "optional": true},
+            "author": {"type": "objectAttribute", "value": {"type": "object", "attributes": {
+                "_ref": {"type": "objectAttribute", "value": {"type": "string"}}
+            }, "dereferencesTo": "author"}}
+        }},
+        {"type": "document", "name": "author", "attributes": {
+            "name": {"type": "objectAttribute", "value": {"type": "string"}}
+        }}
+    ]"#;
+
+    fn infer_query(query: &str) -> Type {
+        infer(&parse(query).unwrap(), &Schema::from_json(SCHEMA).unwrap())
+    }
+
+    /// An object type; keys named with a trailing `?` are optional
+    fn object(fields: &[(&str, Type)]) -> Type {
+        Type::Object(
+            fields
+                .iter()
+                .map(|(name, ty)| Field {
+                    name: name.trim_end_matches('?').to_string(),
+                    ty: ty.clone(),
+                    optional: name.ends_with('?'),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_infer_projection() {
+        // Keys whose value is null are left out: `views` and `author` may be
+        // missing, and `x` never is there
+        let ty = infer_query(
+            r#"*[_type == "post"]{ title, views, "author": author->name, "n": count(*), "x": missing }"#,
+        );
+        assert_eq!(
+            ty,
+            Type::Array(Box::new(object(&[
+                ("title", Type::String),
+                ("views?", Type::Number),
+                ("author?", Type::String),
+                ("n", Type::Number),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_infer_single_document_and_unions() {
+        let ty = infer_query(r#"*[_type == "author"][0]"#);
+        assert_eq!(ty, Type::Document("author".to_string()).nullable());
+
+        let ty = infer_query(
+            r#"*[_type in ["post", "author"]][0]{ "label": coalesce(title, name, "Untitled"), "kind": select(_type == "post" => "p"), "t": _type }"#,
+        );
+        let Type::Union(members) = ty else {
+            panic!("expected a union, got {:?}", ty);
+        };
+        // One object per document type (in name order), and null for an
+        // empty result
+        assert_eq!(members.len(), 3);
+        assert_eq!(
+            members[0],
+            object(&[
+                ("label", Type::String),
+                ("kind?", Type::Literal(Value::from("p"))),
+                ("t", Type::Literal(Value::from("author"))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_infer_narrowing_and_score() {
+        // A filter no document type passes yields an array of nothing
+        let ty = infer_query(r#"*[_type == "post"][_type == "author"]"#);
+        assert_eq!(ty, Type::Array(Box::new(Type::union([]))));
+
+        let ty = infer_query(r#"*[_type == "author"] | score(name match "ada")"#);
+        let Type::Array(element) = ty else {
+            panic!("expected an array, got {:?}", ty);
+        };
+        let Type::Object(fields) = *element else {
+            panic!("expected an object, got {:?}", element);
+        };
+        assert!(fields.iter().any(|f| f.name == "name"));
+        assert_eq!(
+            fields.last().map(|f| (f.name.as_str(), &f.ty)),
+            Some(("_score", &Type::Number))
+        );
+    }
+}
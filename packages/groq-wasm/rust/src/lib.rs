@@ -1,11 +1,50 @@
 //! WASM bindings for GROQ linting and formatting.
 //!
-//! This crate wraps the Rust groq-lint and groq-format libraries,
-//! exposing them to JavaScript via WebAssembly.
+//! This crate wraps the Rust groq-parser, groq-lint and groq-format
+//! libraries, exposing them to JavaScript via WebAssembly.
+
+mod analyze;
+mod ast;
+mod comments;
+mod config;
+mod dataset;
+mod diff;
+mod error;
+mod evaluate;
+mod fingerprint;
+mod fixes;
+mod format_edits;
+mod format_range;
+mod infer;
+mod minify;
+mod param_rules;
+mod positions;
+mod query_tests;
+mod rules;
+mod schema;
+mod schema_rules;
+mod stats;
+mod stats_rules;
+mod thresholds;
+mod trivia;
+mod typegen;
+mod validator;
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+use ast::Walk;
+use config::{LintConfig, Severity};
+use dataset::Dataset;
+pub use error::{ErrorCode, GroqError};
+use fingerprint::FingerprintOptions;
+use positions::{LineIndex, Range, Span};
+use schema::Schema;
+use stats::DatasetStats;
+use validator::ValidatorOptions;
+
 // Initialize panic hook for better error messages
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -20,39 +59,1254 @@ pub struct JsFinding {
     pub rule_id: String,
     pub message: String,
     pub severity: String,
+    /// Byte offsets of the offending code; `None` for findings about the
+    /// query as a whole, such as `unused_param`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<JsSuggestion>,
+    /// Whether `fix` would resolve this finding, i.e. it has a
+    /// machine-applicable suggestion
+    #[serde(default)]
+    pub fixable: bool,
+    /// `start..end` as UTF-16 and line/column positions, if requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+}
+
+/// A suggested fix: replace `start..end` with `replacement`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsSuggestion {
+    pub description: String,
     pub start: usize,
     pub end: usize,
+    pub replacement: String,
+    /// Only `machineApplicable` suggestions are applied by `fix`
+    pub applicability: fixes::Applicability,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+}
+
+impl From<fixes::Suggestion> for JsSuggestion {
+    fn from(s: fixes::Suggestion) -> Self {
+        JsSuggestion {
+            description: s.description,
+            start: s.span.start,
+            end: s.span.end,
+            replacement: s.replacement,
+            applicability: s.applicability,
+            range: None,
+        }
+    }
+}
+
+// TypeScript shapes of the values returned below, emitted into the
+// generated `wasm/groq_wasm.d.ts`. This is their only declaration:
+// `src/types.ts` re-exports them. Keep in sync with the serde attributes
+// on the Js* structs.
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = r#"
+/** Severity levels of groq-lint: high, medium, low */
+export type WasmSeverity = "high" | "medium" | "low";
+
+/** A position computed by the Rust side in every unit consumers need */
+export interface WasmPosition {
+  /** UTF-8 byte offset */
+  utf8: number;
+  /** UTF-16 code unit offset (JavaScript string index) */
+  utf16: number;
+  /** 1-based line */
+  line: number;
+  /** 1-based column, in UTF-16 code units */
+  column: number;
+}
+
+export interface WasmRange {
+  start: WasmPosition;
+  end: WasmPosition;
+}
+
+/** A text edit from the Rust linter: replace `start..end` with `replacement` */
+export interface WasmSuggestion {
+  /** Description of what the edit does */
+  description: string;
+  /** Start byte offset (0-based) */
+  start: number;
+  /** End byte offset (0-based) */
+  end: number;
+  /** The replacement text */
+  replacement: string;
+  /**
+   * `machineApplicable` edits keep the query's results and are applied by
+   * `fix`; `maybeIncorrect` ones may change them and are only suggested
+   */
+  applicability: "machineApplicable" | "maybeIncorrect";
+  /** `start..end` in UTF-16 and line/column terms (with `positions: true`) */
+  range?: WasmRange;
+}
+
+/** A finding from the Rust linter */
+export interface WasmFinding {
+  /** Rule ID (snake_case from Rust) */
+  ruleId: string;
+  /** Human-readable message */
+  message: string;
+  severity: WasmSeverity;
+  /**
+   * Start byte offset (0-based). Absent, like `end` and `range`, for
+   * findings about the query as a whole, such as `unused_param`
+   */
+  start?: number;
+  /** End byte offset (0-based) */
+  end?: number;
+  /** Additional help text */
+  help?: string;
+  /** Suggested edits, each marked with whether `fix` may apply it */
+  suggestions?: WasmSuggestion[];
+  /** Whether `fix` would resolve this finding */
+  fixable: boolean;
+  /** `start..end` in UTF-16 and line/column terms (with `positions: true`) */
+  range?: WasmRange;
+}
+
+/** A `GroqError` returned as data rather than thrown, e.g. for one query of a batch */
+export interface WasmGroqError {
+  code: string;
+  message: string;
+  /** Byte offsets (0-based) of the offending token, if known */
+  span: { start: number; end: number } | null;
+}
+
+/** One query of a `lint_batch` call */
+export interface WasmBatchQuery {
+  /** Key of the query's result, e.g. its file and line */
+  id: string;
+  query: string;
+}
+
+/** Outcome for one query of a batch */
+export type WasmBatchResult = { findings: WasmFinding[] } | { error: WasmGroqError };
+
+/** A suggestion `fix` applied */
+export interface WasmAppliedFix {
+  /** Rule ID (snake_case from Rust) */
+  ruleId: string;
+  /** Description of what the edit does */
+  description: string;
+  /** Start byte offset (0-based) in the query as it was before this pass */
+  start: number;
+  /** End byte offset (0-based) in the query as it was before this pass */
+  end: number;
+  /** The replacement text */
+  replacement: string;
+  /** Zero-based pass the fix was applied in */
+  pass: number;
+}
+
+/** Result of applying every machine-applicable fix to a query */
+export interface WasmFixResult {
+  /** The rewritten query */
+  query: string;
+  /** The fixes applied, in order */
+  fixes: WasmAppliedFix[];
+  /** False if fixes were still being found when `fix` gave up */
+  converged: boolean;
+}
+
+/**
+ * A groq-parser syntax node. `type` is groq-parser's node name; the
+ * remaining fields are that node's children and values, serialized as-is.
+ */
+export interface WasmNode {
+  type: string;
+  /** Byte offsets (0-based) of the node in the query */
+  span: { start: number; end: number };
+  [field: string]: unknown;
+}
+
+/** A parsed query, from `parse` */
+export interface WasmSyntaxTree {
+  /** Bumped whenever the node shapes change */
+  version: number;
+  root: WasmNode;
+}
+
+/** A rule-specific option, beyond `enabled` and `severity` */
+export interface WasmRuleOption {
+  name: string;
+  type: "number";
+  default: number;
+  description: string;
+}
+
+/** Metadata of a rule the WASM linter can report, from `rules` */
+export interface WasmRule {
+  /** Rule ID (snake_case from Rust) */
+  id: string;
+  /** Rule ID in TS convention */
+  kebabId: string;
+  category: "performance" | "correctness";
+  defaultSeverity: WasmSeverity;
+  description: string;
+  /** Documentation page, for rules that have one */
+  docsUrl?: string;
+  /** Whether `fix` can resolve the rule's findings */
+  fixable: boolean;
+  /** Only reported when linting against a schema */
+  requiresSchema: boolean;
+  /** Only reported when the lint config has `params` */
+  requiresParams: boolean;
+  /** Only reported when the lint config has `stats` */
+  requiresStats: boolean;
+  options: WasmRuleOption[];
+}
+
+/** An edit the formatter would make: replace `start..end` with `newText` */
+export interface WasmTextEdit {
+  /** Start offset in UTF-16 code units (a string index) */
+  start: number;
+  /** End offset in UTF-16 code units (a string index) */
+  end: number;
+  /** The replacement text */
+  newText: string;
+  /** `start..end` in UTF-16 and line/column terms */
+  range: WasmRange;
+}
+
+/** The minimal edits that format a query, from `format_edits` */
+export interface WasmFormatEdits {
+  /** The formatted query */
+  formatted: string;
+  /** Edits that turn the query into `formatted`, in input order */
+  edits: WasmTextEdit[];
+  /** The requested offsets, mapped into `formatted` (string indices) */
+  offsets: number[];
+}
+
+/** Result of checking whether a query is already formatted */
+export interface WasmFormatCheck {
+  /** Whether the query matches the formatter's output */
+  formatted: boolean;
+  /** The first edit the formatter would make */
+  firstDifference?: WasmTextEdit;
+  /** Unified diff from the query to the formatted query */
+  diff?: string;
+}
+
+/** A named thing a query uses: a reference it follows or a parameter */
+export interface WasmOccurrence {
+  /** Reference path (e.g. `author`, `tags[]`) or parameter name without `$` */
+  name: string;
+  /** Start byte offset (0-based) */
+  start: number;
+  /** End byte offset (0-based) */
+  end: number;
+  /** `start..end` in UTF-16 and line/column terms */
+  range: WasmRange;
+}
+
+/** A field a query reads */
+export interface WasmFieldRead {
+  /** Dotted path, with `[]` and `->` where the query traverses arrays and references */
+  path: string;
+  /** Where the field is read */
+  context: "filter" | "order" | "projection";
+  /** Start byte offset (0-based) */
+  start: number;
+  /** End byte offset (0-based) */
+  end: number;
+  /** `start..end` in UTF-16 and line/column terms */
+  range: WasmRange;
+}
+
+/** What a query depends on, from `analyze` */
+export interface WasmAnalysis {
+  /** Document types the query filters on, sorted */
+  documentTypes: string[];
+  /** Fields read in filters, `order()` and projections, in source order */
+  fields: WasmFieldRead[];
+  /** References the query follows */
+  references: WasmOccurrence[];
+  /** Parameters the query uses */
+  parameters: WasmOccurrence[];
+  /** Functions the query calls, sorted */
+  functions: string[];
+}
+
+/** Runtime validators for a query's result, from `validator` */
+export interface WasmValidator {
+  /** JSON Schema (draft 2020-12) of the result */
+  jsonSchema: Record<string, unknown>;
+  /** TypeScript module exporting `<name>Schema`, with `zod: true` */
+  zod?: string;
+}
+
+/** A value of a query test's result that differs from the expected one */
+export interface WasmTestDifference {
+  /** JSON pointer into the result (`''` for the whole result) */
+  pointer: string;
+  /** Missing if the result has a value where none was expected */
+  expected?: unknown;
+  /** Missing if the result has no value there */
+  actual?: unknown;
+}
+
+/** Outcome of one query test */
+export interface WasmTestResult {
+  name: string;
+  passed: boolean;
+  /** Why the query could not be run */
+  error?: WasmGroqError;
+  differences: WasmTestDifference[];
+}
+
+/** Outcome of a `run_query_tests` call */
+export interface WasmTestReport {
+  passed: number;
+  failed: number;
+  results: WasmTestResult[];
+}
+
+/** Smallest, largest and mean of a per-document count */
+export interface WasmDistribution {
+  min: number;
+  max: number;
+  mean: number;
+}
+
+/** Statistics of one field across the documents of a type */
+export interface WasmFieldStats {
+  /** Share of the documents that have the field, from 0 to 1 */
+  presence: number;
+  /** Lengths of the field's arrays, for array fields */
+  arrayLength?: WasmDistribution;
+  /** References per document, for fields holding references */
+  references?: WasmDistribution;
+}
+
+/** Statistics of a dataset, from `dataset_stats`, for data-aware linting */
+export interface WasmDatasetStats {
+  /** Total number of documents */
+  documents: number;
+  /** Per `_type`, its document count and field statistics */
+  types: Record<string, { count: number; fields: Record<string, WasmFieldStats> }>;
 }
+"#;
 
-/// Lint a GROQ query and return findings as JSON.
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "WasmFinding[]")]
+    #[derive(Debug)]
+    pub type JsFindings;
+
+    #[wasm_bindgen(typescript_type = "WasmBatchQuery[]")]
+    #[derive(Debug)]
+    pub type JsBatchQueries;
+
+    #[wasm_bindgen(typescript_type = "Record<string, WasmBatchResult>")]
+    #[derive(Debug)]
+    pub type JsBatchResults;
+
+    #[wasm_bindgen(typescript_type = "WasmFixResult")]
+    #[derive(Debug)]
+    pub type JsFixResultValue;
+
+    #[wasm_bindgen(typescript_type = "WasmSyntaxTree")]
+    #[derive(Debug)]
+    pub type JsSyntaxTree;
+
+    #[wasm_bindgen(typescript_type = "WasmRule[]")]
+    #[derive(Debug)]
+    pub type JsRules;
+
+    #[wasm_bindgen(typescript_type = "WasmTextEdit")]
+    #[derive(Debug)]
+    pub type JsTextEditValue;
+
+    #[wasm_bindgen(typescript_type = "WasmFormatEdits")]
+    #[derive(Debug)]
+    pub type JsFormatEditsValue;
+
+    #[wasm_bindgen(typescript_type = "WasmFormatCheck")]
+    #[derive(Debug)]
+    pub type JsFormatCheckValue;
+
+    #[wasm_bindgen(typescript_type = "WasmAnalysis")]
+    #[derive(Debug)]
+    pub type JsAnalysis;
+
+    #[wasm_bindgen(typescript_type = "WasmValidator")]
+    #[derive(Debug)]
+    pub type JsValidator;
+
+    #[wasm_bindgen(typescript_type = "unknown")]
+    #[derive(Debug)]
+    pub type JsQueryResult;
+
+    #[wasm_bindgen(typescript_type = "WasmTestReport")]
+    #[derive(Debug)]
+    pub type JsTestReport;
+
+    #[wasm_bindgen(typescript_type = "WasmDatasetStats")]
+    #[derive(Debug)]
+    pub type JsDatasetStats;
+}
+
+/// Lint a GROQ query and return its findings.
 ///
 /// # Arguments
 /// * `query` - The GROQ query string to lint
+/// * `config` - Optional JSON rule configuration (enabled rules, severity
+///   overrides and rule thresholds, see the `config` module)
+///
+/// # Returns
+/// An array of findings
+#[wasm_bindgen]
+pub fn lint(query: &str, config: Option<String>) -> Result<JsFindings, GroqError> {
+    let config = LintConfig::from_json(config.as_deref()).map_err(GroqError::config)?;
+
+    let js_findings = lint_findings(query, &config)?;
+
+    to_js(&js_findings)
+}
+
+/// Parse a query once and run every enabled rule on it
+fn lint_findings(query: &str, config: &LintConfig) -> Result<Vec<JsFinding>, GroqError> {
+    let root = ast::parse(query)?;
+
+    lint_root(query, &root, None, config)
+}
+
+/// groq-lint's rules and the native rules the config enables, sorted by
+/// position. Findings of rules the config disables are dropped, and rules
+/// given a threshold are checked with it instead (see `thresholds`).
+fn lint_root(
+    query: &str,
+    root: &ast::Node,
+    schema: Option<&Schema>,
+    config: &LintConfig,
+) -> Result<Vec<JsFinding>, GroqError> {
+    let findings =
+        groq_lint::lint(query).map_err(|e| GroqError::internal(format!("Lint error: {}", e)))?;
+
+    let mut js_findings: Vec<JsFinding> = findings
+        .into_iter()
+        .filter(|f| config.is_enabled(&f.rule_id) && config.threshold(&f.rule_id).is_none())
+        // With params, `deep_pagination_param_value` checks supplied offsets
+        // and `missing_param` reports the rest
+        .filter(|f| config.params.is_none() || f.rule_id != "deep_pagination_param")
+        .map(|f| JsFinding {
+            severity: config
+                .severity(&f.rule_id)
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_else(|| format!("{:?}", f.severity).to_lowercase()),
+            rule_id: f.rule_id,
+            message: f.message,
+            start: Some(f.span.start),
+            end: Some(f.span.end),
+            help: None,
+            suggestions: Vec::new(),
+            fixable: false,
+            range: None,
+        })
+        .chain(threshold_findings(query, root, config))
+        .collect();
+
+    attach_fixes(query, root, &mut js_findings);
+
+    if let Some(schema) = schema {
+        js_findings.extend(schema_findings(root, schema, config));
+    }
+    if let Some(params) = &config.params {
+        js_findings.extend(param_findings(root, params, config));
+    }
+    if let Some(stats) = &config.stats {
+        js_findings.extend(stats_findings(root, stats, config));
+    }
+    js_findings.sort_by_key(|f| (f.start, f.end));
+
+    if config.positions {
+        add_ranges(query, &mut js_findings);
+    }
+
+    Ok(js_findings)
+}
+
+/// groq-lint's threshold rules that the config gives a limit, checked with
+/// that limit
+fn threshold_findings(query: &str, root: &ast::Node, config: &LintConfig) -> Vec<JsFinding> {
+    thresholds::THRESHOLD_RULES
+        .iter()
+        .filter(|(rule_id, _)| config.is_enabled(rule_id))
+        .filter_map(|(rule_id, _)| Some((rule_id, config.threshold(rule_id)?)))
+        .flat_map(|(rule_id, threshold)| thresholds::check(rule_id, threshold, query, root))
+        .map(|f| JsFinding {
+            rule_id: f.rule_id.to_string(),
+            message: f.message,
+            severity: config
+                .severity(f.rule_id)
+                .unwrap_or(f.severity)
+                .as_str()
+                .to_string(),
+            start: Some(f.span.start),
+            end: Some(f.span.end),
+            help: None,
+            suggestions: Vec::new(),
+            fixable: false,
+            range: None,
+        })
+        .collect()
+}
+
+/// A finding of one of the wrapper's own rules, with the configured
+/// severity
+fn native_finding(
+    rule_id: &str,
+    message: String,
+    help: String,
+    severity: Severity,
+    span: Option<Span>,
+    config: &LintConfig,
+) -> JsFinding {
+    JsFinding {
+        rule_id: rule_id.to_string(),
+        message,
+        severity: config
+            .severity(rule_id)
+            .unwrap_or(severity)
+            .as_str()
+            .to_string(),
+        start: span.map(|s| s.start),
+        end: span.map(|s| s.end),
+        help: Some(help),
+        suggestions: Vec::new(),
+        fixable: false,
+        range: None,
+    }
+}
+
+/// Check the query's `$params` against the params it will be sent with
+fn param_findings(
+    root: &ast::Node,
+    params: &BTreeMap<String, serde_json::Value>,
+    config: &LintConfig,
+) -> Vec<JsFinding> {
+    let deep_pagination = config
+        .threshold("deep_pagination")
+        .or_else(|| thresholds::default_threshold("deep_pagination"))
+        .unwrap_or(u64::MAX);
+
+    param_rules::check(root, params, deep_pagination)
+        .into_iter()
+        .filter(|f| config.is_enabled(f.rule_id))
+        .map(|f| native_finding(f.rule_id, f.message, f.help, f.severity, f.span, config))
+        .collect()
+}
+
+/// Check the query against statistics of the dataset it will run on
+fn stats_findings(root: &ast::Node, stats: &DatasetStats, config: &LintConfig) -> Vec<JsFinding> {
+    let threshold = |rule_id: &str| {
+        config
+            .threshold(rule_id)
+            .or_else(|| stats_rules::default_threshold(rule_id))
+            .unwrap_or(u64::MAX)
+    };
+
+    stats_rules::check(root, stats, &threshold)
+        .into_iter()
+        .filter(|f| config.is_enabled(f.rule_id))
+        .map(|f| {
+            native_finding(
+                f.rule_id,
+                f.message,
+                f.help,
+                f.severity,
+                Some(f.span),
+                config,
+            )
+        })
+        .collect()
+}
+
+/// Check the query against the schema
+fn schema_findings(root: &ast::Node, schema: &Schema, config: &LintConfig) -> Vec<JsFinding> {
+    schema_rules::check(root, schema)
+        .into_iter()
+        .filter(|f| config.is_enabled(f.rule_id))
+        .map(|f| {
+            native_finding(
+                f.rule_id,
+                f.message,
+                f.help,
+                f.severity,
+                Some(f.span),
+                config,
+            )
+        })
+        .collect()
+}
+
+/// Fill in `range` on findings and their suggestions
+fn add_ranges(query: &str, findings: &mut [JsFinding]) {
+    let index = LineIndex::new(query);
+    for finding in findings {
+        if let (Some(start), Some(end)) = (finding.start, finding.end) {
+            finding.range = Some(index.range(Span::new(start, end)));
+        }
+        for suggestion in &mut finding.suggestions {
+            suggestion.range = Some(index.range(Span::new(suggestion.start, suggestion.end)));
+        }
+    }
+}
+
+/// One query of a `lint_batch` call
+#[derive(Deserialize)]
+pub struct JsBatchQuery {
+    pub id: String,
+    pub query: String,
+}
+
+/// Outcome for one query of a batch: a failure doesn't fail the batch
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JsBatchResult {
+    Findings(Vec<JsFinding>),
+    Error(GroqError),
+}
+
+/// Lint many queries in one call.
+///
+/// The configuration is parsed once and the boundary is crossed once per
+/// batch rather than once per query, which matters when linting thousands
+/// of extracted queries.
+///
+/// # Arguments
+/// * `queries` - An array of `{ id, query }` records
+/// * `config` - Optional JSON rule configuration, as for `lint`
 ///
 /// # Returns
-/// A JSON string containing an array of findings
+/// An object mapping each id to `{ findings: [...] }` or `{ error: {...} }`
 #[wasm_bindgen]
-pub fn lint(query: &str) -> Result<String, JsValue> {
-    match groq_lint::lint(query) {
-        Ok(findings) => {
-            let js_findings: Vec<JsFinding> = findings
+pub fn lint_batch(
+    queries: JsBatchQueries,
+    config: Option<String>,
+) -> Result<JsBatchResults, GroqError> {
+    let queries = from_js_batch(queries)?;
+    let config = LintConfig::from_json(config.as_deref()).map_err(GroqError::config)?;
+
+    to_js(&batch_findings(queries, |query| {
+        lint_findings(query, &config)
+    }))
+}
+
+fn from_js_batch(queries: JsBatchQueries) -> Result<Vec<JsBatchQuery>, GroqError> {
+    serde_wasm_bindgen::from_value(queries.into())
+        .map_err(|e| GroqError::config(format!("invalid batch: {}", e)))
+}
+
+fn batch_findings(
+    queries: Vec<JsBatchQuery>,
+    lint_one: impl Fn(&str) -> Result<Vec<JsFinding>, GroqError>,
+) -> BTreeMap<String, JsBatchResult> {
+    queries
+        .into_iter()
+        .map(|q| {
+            let result = match lint_one(&q.query) {
+                Ok(findings) => JsBatchResult::Findings(findings),
+                Err(error) => JsBatchResult::Error(error),
+            };
+            (q.id, result)
+        })
+        .collect()
+}
+
+/// A fix applied by `fix`, with offsets into the query as it was before
+/// that pass
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsAppliedFix {
+    pub rule_id: String,
+    pub description: String,
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+    /// Zero-based pass the fix was applied in
+    pub pass: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsFixResult {
+    pub query: String,
+    pub fixes: Vec<JsAppliedFix>,
+    /// False if fixes were still being found after `MAX_FIX_PASSES`
+    pub converged: bool,
+}
+
+/// Upper bound on lint/fix rounds, in case two fixes undo each other
+const MAX_FIX_PASSES: usize = 10;
+
+/// Apply every machine-applicable fix to a GROQ query.
+///
+/// Each pass lints the query, applies the first machine-applicable
+/// suggestion of every finding that doesn't overlap an earlier one, and lints
+/// again, until no fixes are left. Suggestions that may change results, such
+/// as `match-on-id`'s, are left for the user.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to fix
+/// * `config` - Optional JSON rule configuration, as for `lint`; disabled
+///   rules are not fixed
+///
+/// # Returns
+/// `{ query, fixes, converged }` with the rewritten query, the fixes applied
+/// in order, and whether it stopped because nothing was left to fix rather
+/// than after `MAX_FIX_PASSES` passes
+#[wasm_bindgen]
+pub fn fix(query: &str, config: Option<String>) -> Result<JsFixResultValue, GroqError> {
+    let config = LintConfig::from_json(config.as_deref()).map_err(GroqError::config)?;
+
+    let result = fix_query(query, &config)?;
+
+    to_js(&result)
+}
+
+fn fix_query(query: &str, config: &LintConfig) -> Result<JsFixResult, GroqError> {
+    let mut query = query.to_string();
+    let mut applied = Vec::new();
+    let mut converged = false;
+
+    for pass in 0..MAX_FIX_PASSES {
+        let mut candidates: Vec<JsAppliedFix> = lint_findings(&query, config)?
+            .into_iter()
+            .filter_map(|f| {
+                let rule_id = f.rule_id;
+                f.suggestions
+                    .into_iter()
+                    .find(|s| s.applicability == fixes::Applicability::MachineApplicable)
+                    .map(|s| JsAppliedFix {
+                        rule_id,
+                        description: s.description,
+                        start: s.start,
+                        end: s.end,
+                        replacement: s.replacement,
+                        pass,
+                    })
+            })
+            .collect();
+        candidates.sort_by_key(|f| (f.start, f.end));
+
+        let mut fixes: Vec<JsAppliedFix> = Vec::new();
+        for candidate in candidates {
+            if fixes.last().is_none_or(|last| last.end <= candidate.start) {
+                fixes.push(candidate);
+            }
+        }
+        if fixes.is_empty() {
+            converged = true;
+            break;
+        }
+
+        for f in fixes.iter().rev() {
+            query.replace_range(f.start..f.end, &f.replacement);
+        }
+        applied.extend(fixes);
+    }
+
+    Ok(JsFixResult {
+        query,
+        fixes: applied,
+        converged,
+    })
+}
+
+/// Add help text and fixes to findings of the rules `fixes` knows about.
+///
+/// Each finding is paired with the site of its rule at the same span, or
+/// else the first unclaimed one overlapping it. A finding no site matches
+/// gets help text but no suggestion.
+fn attach_fixes(query: &str, root: &ast::Node, findings: &mut [JsFinding]) {
+    let mut sites: BTreeMap<String, Vec<Option<fixes::Site>>> = BTreeMap::new();
+    for finding in findings.iter_mut() {
+        let Some(help) = fixes::help(&finding.rule_id) else {
+            continue;
+        };
+        finding.help = Some(help.to_string());
+
+        let rule_sites = sites.entry(finding.rule_id.clone()).or_insert_with(|| {
+            fixes::sites(&finding.rule_id, root, query)
                 .into_iter()
-                .map(|f| JsFinding {
-                    rule_id: f.rule_id,
-                    message: f.message,
-                    severity: format!("{:?}", f.severity).to_lowercase(),
-                    start: f.span.start,
-                    end: f.span.end,
-                })
-                .collect();
-
-            serde_json::to_string(&js_findings)
-                .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+                .map(Some)
+                .collect()
+        });
+
+        let (Some(start), Some(end)) = (finding.start, finding.end) else {
+            continue;
+        };
+        let span = Span::new(start, end);
+        let exact = rule_sites
+            .iter()
+            .position(|site| site.as_ref().is_some_and(|s| s.span == span));
+        let overlapping = || {
+            rule_sites.iter().position(|site| {
+                site.as_ref()
+                    .is_some_and(|s| s.span.start < span.end && span.start < s.span.end)
+            })
+        };
+        let claimed = exact
+            .or_else(overlapping)
+            .and_then(|i| rule_sites[i].take());
+
+        if let Some(suggestion) = claimed.and_then(|site| site.suggestion) {
+            finding.fixable = suggestion.applicability == fixes::Applicability::MachineApplicable;
+            finding.suggestions.push(suggestion.into());
+        }
+    }
+}
+
+/// Lint a GROQ query, including the schema-aware rules.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to lint
+/// * `schema_json` - The `schema.json` produced by `sanity schema extract`
+/// * `config` - Optional JSON rule configuration, as for `lint`
+///
+/// # Returns
+/// An array of findings
+#[wasm_bindgen]
+pub fn lint_with_schema(
+    query: &str,
+    schema_json: &str,
+    config: Option<String>,
+) -> Result<JsFindings, GroqError> {
+    let config = LintConfig::from_json(config.as_deref()).map_err(GroqError::config)?;
+    let schema = Schema::from_json(schema_json).map_err(GroqError::schema)?;
+
+    let js_findings = lint_findings_with_schema(query, &schema, &config)?;
+
+    to_js(&js_findings)
+}
+
+/// Parse a query once and run every enabled rule on it, including the
+/// schema rules
+fn lint_findings_with_schema(
+    query: &str,
+    schema: &Schema,
+    config: &LintConfig,
+) -> Result<Vec<JsFinding>, GroqError> {
+    let root = ast::parse(query)?;
+
+    lint_root(query, &root, Some(schema), config)
+}
+
+/// A parsed and indexed schema that can be reused across calls.
+///
+/// Building the index is the expensive part of schema-aware linting, so the
+/// language server keeps one of these alive and calls `update` whenever
+/// `schema.json` changes on disk.
+#[wasm_bindgen(js_name = Schema)]
+pub struct SchemaHandle {
+    schema: Schema,
+    source_hash: u64,
+}
+
+#[wasm_bindgen(js_class = Schema)]
+impl SchemaHandle {
+    /// Parse and index a `schema.json` document
+    #[wasm_bindgen(constructor)]
+    pub fn new(schema_json: &str) -> Result<SchemaHandle, GroqError> {
+        let schema = Schema::from_json(schema_json).map_err(GroqError::schema)?;
+
+        Ok(SchemaHandle {
+            schema,
+            source_hash: fnv1a(schema_json.as_bytes()),
+        })
+    }
+
+    /// Replace the schema with new JSON.
+    ///
+    /// Returns `false` without re-indexing when the JSON is unchanged. On a
+    /// parse error the previous schema is kept.
+    pub fn update(&mut self, schema_json: &str) -> Result<bool, GroqError> {
+        let source_hash = fnv1a(schema_json.as_bytes());
+        if source_hash == self.source_hash {
+            return Ok(false);
         }
-        Err(e) => Err(JsValue::from_str(&format!("Lint error: {}", e))),
+
+        *self = SchemaHandle::new(schema_json)?;
+        Ok(true)
+    }
+
+    /// Names of all document types, sorted
+    pub fn document_types(&self) -> Vec<String> {
+        self.schema
+            .document_types()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Field names of a document type (empty for unknown types)
+    pub fn fields_for_type(&self, type_name: &str) -> Vec<String> {
+        self.schema
+            .fields_for_type(type_name)
+            .unwrap_or_default()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Document types a reference field can point at
+    pub fn reference_targets(&self, type_name: &str, field: &str) -> Vec<String> {
+        self.schema
+            .reference_targets(type_name, field)
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Lint a query against this schema, as `lint_with_schema` does
+    pub fn lint(&self, query: &str, config: Option<String>) -> Result<JsFindings, GroqError> {
+        let config = LintConfig::from_json(config.as_deref()).map_err(GroqError::config)?;
+
+        let js_findings = lint_findings_with_schema(query, &self.schema, &config)?;
+
+        to_js(&js_findings)
+    }
+
+    /// Generate TypeScript for a query's result, as `typegen` does
+    pub fn typegen(&self, query: &str, type_name: Option<String>) -> Result<String, GroqError> {
+        typegen_with(query, &self.schema, type_name.as_deref())
     }
+
+    /// Build validators for a query's result, as `validator` does
+    pub fn validator(
+        &self,
+        query: &str,
+        options: Option<String>,
+    ) -> Result<JsValidator, GroqError> {
+        let options = ValidatorOptions::from_json(options.as_deref()).map_err(GroqError::config)?;
+
+        to_js(&validator_with(query, &self.schema, &options)?)
+    }
+
+    /// Lint many queries against this schema, as `lint_batch` does
+    pub fn lint_batch(
+        &self,
+        queries: JsBatchQueries,
+        config: Option<String>,
+    ) -> Result<JsBatchResults, GroqError> {
+        let queries = from_js_batch(queries)?;
+        let config = LintConfig::from_json(config.as_deref()).map_err(GroqError::config)?;
+
+        to_js(&batch_findings(queries, |query| {
+            lint_findings_with_schema(query, &self.schema, &config)
+        }))
+    }
+}
+
+/// Documents loaded from an NDJSON export that can be queried repeatedly.
+///
+/// Parsing a large export dominates the cost of `evaluate`, so tests that
+/// run many queries against the same fixture data load it once here.
+#[wasm_bindgen(js_name = Dataset)]
+pub struct DatasetHandle {
+    dataset: Dataset,
 }
 
+#[wasm_bindgen(js_class = Dataset)]
+impl DatasetHandle {
+    /// Load documents from NDJSON, one document per line
+    #[wasm_bindgen(constructor)]
+    pub fn new(ndjson: &str) -> Result<DatasetHandle, GroqError> {
+        let dataset = Dataset::from_ndjson(ndjson).map_err(GroqError::dataset)?;
+
+        Ok(DatasetHandle { dataset })
+    }
+
+    /// Number of documents in the dataset
+    pub fn size(&self) -> usize {
+        self.dataset.documents().len()
+    }
+
+    /// Statistics of these documents, as `dataset_stats` computes them
+    pub fn stats(&self) -> Result<JsDatasetStats, GroqError> {
+        to_js(&DatasetStats::from_dataset(&self.dataset))
+    }
+
+    /// Evaluate a query against these documents, as `evaluate` does
+    pub fn evaluate(
+        &self,
+        query: &str,
+        params: Option<String>,
+    ) -> Result<JsQueryResult, GroqError> {
+        to_js(&evaluate_with(query, params.as_deref(), &self.dataset)?)
+    }
+}
+
+/// Convert a value to a plain JS object typed as `R` on the TypeScript side
+fn to_js<T: Serialize, R: JsCast>(value: &T) -> Result<R, GroqError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map(JsCast::unchecked_into)
+        .map_err(|e| GroqError::internal(format!("Serialization error: {}", e)))
+}
+
+/// 64-bit FNV-1a, used to detect unchanged inputs cheaply and for query
+/// fingerprints
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Metadata for every rule `lint` and `lint_with_schema` can report.
+///
+/// # Returns
+/// An array of rules with their ids, category, default severity,
+/// description, docs URL, whether they are fixable and their options
+#[wasm_bindgen]
+pub fn rules() -> Result<JsRules, GroqError> {
+    to_js(&rules::registry())
+}
+
+/// Parse a GROQ query and return its syntax tree as JSON.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to parse
+///
+/// # Returns
+/// `{ version: 1, root: <node> }`, where `root` is groq-parser's tree
+/// serialized as-is, with groq-parser's node names; see the `ast` module
+#[wasm_bindgen]
+pub fn parse(query: &str) -> Result<JsSyntaxTree, GroqError> {
+    to_js(&syntax_tree(query)?)
+}
+
+fn syntax_tree(query: &str) -> Result<ast::SyntaxTree, GroqError> {
+    Ok(ast::SyntaxTree {
+        version: ast::AST_VERSION,
+        root: ast::parse(query)?,
+    })
+}
+
+/// Check that a GROQ query parses, without building anything from it.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to check
+///
+/// # Returns
+/// Nothing; fails with a `PARSE_ERROR` if the query is not valid GROQ
+#[wasm_bindgen]
+pub fn validate(query: &str) -> Result<(), GroqError> {
+    ast::parse(query).map(|_| ())
+}
+
+/// Report what a GROQ query depends on.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to analyze
+///
+/// # Returns
+/// The document types it filters on, the fields it reads in filters,
+/// `order()` and projections, the references it follows, the parameters
+/// it uses and the functions it calls, with spans where they apply
+#[wasm_bindgen]
+pub fn analyze(query: &str) -> Result<JsAnalysis, GroqError> {
+    let root = ast::parse(query)?;
+
+    to_js(&analyze::analyze(&root, query))
+}
+
+/// Generate TypeScript declarations for the result of a GROQ query.
+///
+/// The result type is inferred from the schema: projections become object
+/// types, `[0]` a single document or `null`, `->` the referenced document
+/// types, and `coalesce()`/`select()` unions of their branches. Values that
+/// can't be inferred are `unknown`.
+///
+/// # Arguments
+/// * `query` - The GROQ query string
+/// * `schema_json` - The `schema.json` from `sanity schema extract`
+/// * `type_name` - Name of the result type, `QueryResult` by default
+///
+/// # Returns
+/// `export type` and `export interface` declarations
+#[wasm_bindgen]
+pub fn typegen(
+    query: &str,
+    schema_json: &str,
+    type_name: Option<String>,
+) -> Result<String, GroqError> {
+    let schema = Schema::from_json(schema_json).map_err(GroqError::schema)?;
+
+    typegen_with(query, &schema, type_name.as_deref())
+}
+
+fn typegen_with(
+    query: &str,
+    schema: &Schema,
+    type_name: Option<&str>,
+) -> Result<String, GroqError> {
+    let type_name = type_name.unwrap_or("QueryResult");
+    if !typegen::is_identifier(type_name) {
+        return Err(GroqError::config(format!(
+            "Invalid type name \"{}\"",
+            type_name
+        )));
+    }
+    let root = ast::parse(query)?;
+
+    let ty = infer::infer(&root, schema);
+    Ok(typegen::declarations(&ty, type_name, schema))
+}
+
+/// Validators for the result of a GROQ query
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsValidatorOutput {
+    pub json_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zod: Option<String>,
+}
+
+/// Build runtime validators for the result of a GROQ query.
+///
+/// The result type is inferred as for `typegen`. Keys whose value can be
+/// `null` are not required, since Content Lake omits null attributes.
+///
+/// # Arguments
+/// * `query` - The GROQ query string
+/// * `schema_json` - The `schema.json` from `sanity schema extract`
+/// * `options` - Optional JSON options: `name` (default `QueryResult`) and
+///   `zod` to also emit a Zod snippet
+///
+/// # Returns
+/// `{ jsonSchema, zod? }`
+#[wasm_bindgen]
+pub fn validator(
+    query: &str,
+    schema_json: &str,
+    options: Option<String>,
+) -> Result<JsValidator, GroqError> {
+    let options = ValidatorOptions::from_json(options.as_deref()).map_err(GroqError::config)?;
+    let schema = Schema::from_json(schema_json).map_err(GroqError::schema)?;
+
+    to_js(&validator_with(query, &schema, &options)?)
+}
+
+fn validator_with(
+    query: &str,
+    schema: &Schema,
+    options: &ValidatorOptions,
+) -> Result<JsValidatorOutput, GroqError> {
+    if !typegen::is_identifier(&options.name) {
+        return Err(GroqError::config(format!(
+            "Invalid type name \"{}\"",
+            options.name
+        )));
+    }
+    let root = ast::parse(query)?;
+
+    let ty = infer::infer(&root, schema);
+    Ok(JsValidatorOutput {
+        json_schema: validator::json_schema(&ty, &options.name, schema),
+        zod: options
+            .zod
+            .then(|| validator::zod(&ty, &options.name, schema)),
+    })
+}
+
+/// Evaluate a GROQ query against documents from a dataset export.
+///
+/// Supports filters, projections, `->`, `order()`, slices and the common
+/// functions; anything unsupported fails with `EVALUATION_ERROR` (see the
+/// `evaluate` module for the full list).
+///
+/// # Arguments
+/// * `query` - The GROQ query string
+/// * `params` - Optional JSON object of query params, without the `$`
+/// * `dataset` - NDJSON, one document per line, as in a `sanity dataset
+///   export` archive
+///
+/// # Returns
+/// The query result
+#[wasm_bindgen]
+pub fn evaluate(
+    query: &str,
+    params: Option<String>,
+    dataset: &str,
+) -> Result<JsQueryResult, GroqError> {
+    let dataset = Dataset::from_ndjson(dataset).map_err(GroqError::dataset)?;
+
+    to_js(&evaluate_with(query, params.as_deref(), &dataset)?)
+}
+
+fn evaluate_with(
+    query: &str,
+    params: Option<&str>,
+    dataset: &Dataset,
+) -> Result<serde_json::Value, GroqError> {
+    let params: BTreeMap<String, serde_json::Value> = match params {
+        Some(params) => serde_json::from_str(params)
+            .map_err(|e| GroqError::config(format!("Invalid params: {}", e)))?,
+        None => BTreeMap::new(),
+    };
+    let root = ast::parse(query)?;
+
+    Ok(evaluate::evaluate(&root, &params, dataset)?)
+}
+
+/// Compute statistics of a dataset export for data-aware linting.
+///
+/// Pass the result as the `stats` key of a lint config to enable the
+/// stats rules (`unset_field_filter`, `large_order`, `large_array_deref`).
+///
+/// # Arguments
+/// * `dataset` - NDJSON, one document per line
+///
+/// # Returns
+/// Document counts per `_type`, and per field its presence ratio, array
+/// lengths and reference fan-out (see the `stats` module)
+#[wasm_bindgen]
+pub fn dataset_stats(dataset: &str) -> Result<JsDatasetStats, GroqError> {
+    let dataset = Dataset::from_ndjson(dataset).map_err(GroqError::dataset)?;
+
+    to_js(&DatasetStats::from_dataset(&dataset))
+}
+
+/// Run query tests against fixture documents.
+///
+/// The spec lists documents and tests, each with a query, optional params
+/// and the expected result (`expect`) or values at JSON pointers in it
+/// (`expectAt`); see the `query_tests` module for the format.
+///
+/// # Arguments
+/// * `spec` - The spec as JSON or YAML
+///
+/// # Returns
+/// Pass and fail counts, and for each test any error and the differences
+/// from the expected result
+#[wasm_bindgen]
+pub fn run_query_tests(spec: &str) -> Result<JsTestReport, GroqError> {
+    to_js(&query_test_report(spec)?)
+}
+
+fn query_test_report(spec: &str) -> Result<query_tests::Report, GroqError> {
+    let spec = query_tests::Spec::parse(spec)
+        .map_err(|e| GroqError::config(format!("Invalid test spec: {}", e)))?;
+
+    spec.run().map_err(GroqError::dataset)
+}
+
+/// Line width `format` and friends use when none is given
+const DEFAULT_WIDTH: usize = 80;
+
 /// Format a GROQ query.
 ///
 /// # Arguments
@@ -62,11 +1316,305 @@ pub fn lint(query: &str) -> Result<String, JsValue> {
 /// # Returns
 /// The formatted query string
 #[wasm_bindgen]
-pub fn format(query: &str, width: Option<usize>) -> Result<String, JsValue> {
-    let width = width.unwrap_or(80);
+pub fn format(query: &str, width: Option<usize>) -> Result<String, GroqError> {
+    format_with(query, width.unwrap_or(DEFAULT_WIDTH))
+}
+
+fn format_with(query: &str, width: usize) -> Result<String, GroqError> {
+    // Syntax errors come from groq-parser with their position; anything
+    // groq-format rejects after that is a bug on its side
+    ast::parse(query)?;
+    let formatted = groq_format::format_query(query, width)
+        .map_err(|e| GroqError::internal(format!("Format error: {:?}", e)))?;
+
+    Ok(comments::restore(query, &formatted))
+}
+
+/// Minify a GROQ query.
+///
+/// Comments and all whitespace that doesn't separate tokens are removed.
+/// The result is parsed again and checked to give the same tree as the
+/// input, so a minifier bug fails loudly instead of changing the query.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to minify
+///
+/// # Returns
+/// The shortest form of the query
+#[wasm_bindgen]
+pub fn minify(query: &str) -> Result<String, GroqError> {
+    let root = ast::parse(query)?;
+
+    let minified = minify::minify(query);
+    let same = ast::parse(&minified)
+        .is_ok_and(|minified_root| minify::shape(&minified_root) == minify::shape(&root));
+    if !same {
+        return Err(GroqError::internal(format!(
+            "Minifying changed the query: {}",
+            minified
+        )));
+    }
+    Ok(minified)
+}
+
+/// Fingerprint the structure of a GROQ query.
+///
+/// Queries that differ only in whitespace, comments or redundant
+/// parentheses share a fingerprint.
+/// Options can also ignore the order of `&&`/`||` operands and the values
+/// of literals (see the `fingerprint` module).
+///
+/// # Arguments
+/// * `query` - The GROQ query string
+/// * `options` - Optional JSON options: `ignoreFilterOrder`,
+///   `literalPlaceholders`
+///
+/// # Returns
+/// A 64-bit hash of the normalized query, as 16 hex digits
+#[wasm_bindgen]
+pub fn fingerprint(query: &str, options: Option<String>) -> Result<String, GroqError> {
+    let options = FingerprintOptions::from_json(options.as_deref()).map_err(GroqError::config)?;
+    let root = ast::parse(query)?;
+
+    let normalized = fingerprint::normalize(&root, &options);
+    Ok(format!("{:016x}", fnv1a(normalized.as_bytes())))
+}
+
+/// A replacement of `start..end` (UTF-16 code units) with `newText`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsTextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub new_text: String,
+    pub range: Range,
+}
+
+impl JsTextEdit {
+    fn new(index: &LineIndex, span: Span, new_text: String) -> Self {
+        let range = index.range(span);
+        JsTextEdit {
+            start: range.start.utf16,
+            end: range.end.utf16,
+            new_text,
+            range,
+        }
+    }
+}
+
+/// Format only the part of a query covering a selection.
+///
+/// The selection is widened to the smallest subtree that can be formatted
+/// on its own, such as a projection or a filter expression. Everything
+/// outside that subtree is left byte-identical.
+///
+/// # Arguments
+/// * `query` - The GROQ query string
+/// * `start` - Start of the selection, in UTF-16 code units (a JavaScript
+///   string index, as editors and the LSP report it)
+/// * `end` - End of the selection, in UTF-16 code units
+/// * `width` - Maximum line width (default: 80)
+///
+/// # Returns
+/// A single text edit replacing the subtree with its formatted text, with
+/// `start`/`end` in UTF-16 code units
+#[wasm_bindgen]
+pub fn format_range(
+    query: &str,
+    start: usize,
+    end: usize,
+    width: Option<usize>,
+) -> Result<JsTextEditValue, GroqError> {
+    to_js(&format_range_with(
+        query,
+        start,
+        end,
+        width.unwrap_or(DEFAULT_WIDTH),
+    )?)
+}
+
+fn format_range_with(
+    query: &str,
+    start: usize,
+    end: usize,
+    width: usize,
+) -> Result<JsTextEdit, GroqError> {
+    let index = LineIndex::new(query);
+    let selection = Span::new(
+        utf8_offset(&index, query, start)?,
+        utf8_offset(&index, query, end)?,
+    );
+    if selection.start > selection.end {
+        return Err(invalid_range(query, start, end));
+    }
+    let root = ast::parse(query)?;
+    let node = format_range::covering_node(&root, selection);
+
+    let (indent, column) = format_range::line_indent(query, node.span.start);
+    let subtree_width = width.saturating_sub(column).max(1);
+    let formatted = format_with(&query[node.span.start..node.span.end], subtree_width)?;
+
+    Ok(JsTextEdit::new(
+        &index,
+        node.span(),
+        format_range::indent_continuation(&formatted, indent),
+    ))
+}
+
+/// Formatting result as edits against the input
+#[derive(Debug, Serialize)]
+pub struct JsFormatEdits {
+    pub formatted: String,
+    pub edits: Vec<JsTextEdit>,
+    /// The requested input offsets, mapped into `formatted` (UTF-16)
+    pub offsets: Vec<usize>,
+}
+
+/// Format a query and return the minimal edits that turn it into the
+/// formatted query.
+///
+/// Editors can apply the edits instead of replacing the whole document,
+/// which keeps undo history small and markers in untouched text in place.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to format
+/// * `width` - Maximum line width (default: 80)
+/// * `offsets` - Offsets in `query` to carry over, such as the cursor and
+///   selection anchors, in UTF-16 code units (JavaScript string indices)
+///
+/// # Returns
+/// The formatted query, the edits in input order, and each offset mapped
+/// into the formatted query, again in UTF-16 code units. Edit `start`/`end`
+/// are UTF-16 code units too
+#[wasm_bindgen]
+pub fn format_edits(
+    query: &str,
+    width: Option<usize>,
+    offsets: Option<Vec<usize>>,
+) -> Result<JsFormatEditsValue, GroqError> {
+    to_js(&format_edits_with(
+        query,
+        width.unwrap_or(DEFAULT_WIDTH),
+        offsets.as_deref().unwrap_or_default(),
+    )?)
+}
+
+fn format_edits_with(
+    query: &str,
+    width: usize,
+    offsets: &[usize],
+) -> Result<JsFormatEdits, GroqError> {
+    let index = LineIndex::new(query);
+    let offsets = offsets
+        .iter()
+        .map(|&offset| utf8_offset(&index, query, offset))
+        .collect::<Result<Vec<_>, _>>()?;
+    let formatted = format_with(query, width)?;
+    let edits = format_edits::edits(query, &formatted);
 
-    groq_format::format_query(query, width)
-        .map_err(|e| JsValue::from_str(&format!("Format error: {:?}", e)))
+    let formatted_index = LineIndex::new(&formatted);
+    Ok(JsFormatEdits {
+        offsets: offsets
+            .iter()
+            .map(|&offset| {
+                formatted_index
+                    .position(format_edits::map_offset(&edits, offset))
+                    .utf16
+            })
+            .collect(),
+        edits: edits
+            .iter()
+            .map(|edit| {
+                JsTextEdit::new(
+                    &index,
+                    edit.old,
+                    formatted[edit.new.start..edit.new.end].to_string(),
+                )
+            })
+            .collect(),
+        formatted,
+    })
+}
+
+/// Lines of unchanged context around each hunk of a `check_format` diff
+const DIFF_CONTEXT: usize = 3;
+
+/// Whether a query is formatted, and how it differs if it isn't
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsFormatCheck {
+    pub formatted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_difference: Option<JsTextEdit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Check whether a query already matches the formatter's output, without
+/// rewriting it.
+///
+/// # Arguments
+/// * `query` - The GROQ query string to check
+/// * `width` - Maximum line width (default: 80)
+///
+/// # Returns
+/// `{ formatted: true }`, or the first edit the formatter would make and a
+/// unified diff from the query to the formatted query
+#[wasm_bindgen]
+pub fn check_format(query: &str, width: Option<usize>) -> Result<JsFormatCheckValue, GroqError> {
+    to_js(&check_format_with(query, width.unwrap_or(DEFAULT_WIDTH))?)
+}
+
+fn check_format_with(query: &str, width: usize) -> Result<JsFormatCheck, GroqError> {
+    let formatted = format_with(query, width)?;
+    if formatted == query {
+        return Ok(JsFormatCheck {
+            formatted: true,
+            first_difference: None,
+            diff: None,
+        });
+    }
+
+    let first_difference = format_edits::edits(query, &formatted)
+        .into_iter()
+        .next()
+        .map(|edit| {
+            JsTextEdit::new(
+                &LineIndex::new(query),
+                edit.old,
+                formatted[edit.new.start..edit.new.end].to_string(),
+            )
+        });
+    Ok(JsFormatCheck {
+        formatted: false,
+        first_difference,
+        diff: Some(diff::unified(
+            query,
+            &formatted,
+            "input",
+            "formatted",
+            DIFF_CONTEXT,
+        )),
+    })
+}
+
+/// Byte offset of a UTF-16 offset passed in from JavaScript
+fn utf8_offset(index: &LineIndex, query: &str, utf16: usize) -> Result<usize, GroqError> {
+    index
+        .utf8_offset(utf16)
+        .ok_or_else(|| invalid_range(query, utf16, utf16))
+}
+
+fn invalid_range(query: &str, start: usize, end: usize) -> GroqError {
+    GroqError::new(
+        ErrorCode::InvalidRange,
+        format!(
+            "Range {}..{} is not valid for a query of {} UTF-16 code units",
+            start,
+            end,
+            query.encode_utf16().count()
+        ),
+    )
 }
 
 #[cfg(test)]
@@ -75,15 +1623,464 @@ mod tests {
 
     #[test]
     fn test_lint_valid_query() {
-        let result = lint("*[_type == \"post\"]");
-        assert!(result.is_ok());
-        let findings: Vec<JsFinding> = serde_json::from_str(&result.unwrap()).unwrap();
+        let findings = lint_findings("*[_type == \"post\"]", &LintConfig::default()).unwrap();
+        assert!(findings.is_empty());
+    }
+
+    #[test]
+    fn test_lint_config_rules() {
+        let query = "*[_type == \"post\"]{ \"a\": author->_id }";
+        let config = r#"{"rules": {"join-to-get-id": {"severity": "error"}}}"#;
+        let findings = lint_findings(query, &LintConfig::from_json(Some(config)).unwrap()).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule_id, "join_to_get_id");
+        assert_eq!(findings[0].severity, "high");
+
+        let config = r#"{"rules": {"join_to_get_id": false}}"#;
+        let findings = lint_findings(query, &LintConfig::from_json(Some(config)).unwrap()).unwrap();
         assert!(findings.is_empty());
     }
 
+    #[test]
+    fn test_lint_applies_thresholds() {
+        let query = "*[_type == \"post\"][1500...1520]";
+        let rule_ids = |config: &str| -> Vec<String> {
+            lint_findings(query, &LintConfig::from_json(Some(config)).unwrap())
+                .unwrap()
+                .into_iter()
+                .map(|f| f.rule_id)
+                .collect()
+        };
+        assert_eq!(rule_ids("{}"), vec!["deep_pagination"]);
+        // The configured limit replaces groq-lint's, without a duplicate
+        assert!(rule_ids(r#"{"rules": {"deep_pagination": {"threshold": 2000}}}"#).is_empty());
+        assert_eq!(
+            rule_ids(r#"{"rules": {"deep-pagination": {"threshold": 1500}}}"#),
+            vec!["deep_pagination"]
+        );
+        assert!(
+            rule_ids(r#"{"rules": {"deep_pagination": {"enabled": false, "threshold": 10}}}"#)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_lint_suggests_fixes() {
+        let query = "*[_type == \"post\"]{ \"authorId\": author->_id }";
+        let findings = lint_findings(query, &LintConfig::default()).unwrap();
+        let finding = findings
+            .iter()
+            .find(|f| f.rule_id == "join_to_get_id")
+            .unwrap();
+        assert!(finding.help.is_some());
+        assert_eq!(finding.suggestions.len(), 1);
+        let fix = &finding.suggestions[0];
+        assert_eq!(&query[fix.start..fix.end], "->_id");
+        assert_eq!(fix.replacement, "._ref");
+        assert!(finding.fixable);
+
+        // A suggestion that needs review doesn't make a finding fixable
+        let findings = lint_findings("*[_id match \"abc\"]", &LintConfig::default()).unwrap();
+        assert_eq!(findings[0].rule_id, "match_on_id");
+        assert_eq!(findings[0].suggestions.len(), 1);
+        assert!(!findings[0].fixable);
+    }
+
+    #[test]
+    fn test_attach_fixes_matches_by_span() {
+        let query = "*[_type == \"post\"]{ \"a\": author->_id }";
+        let root = ast::parse(query).unwrap();
+        let finding = |start: usize, end: usize| JsFinding {
+            rule_id: "join_to_get_id".to_string(),
+            message: String::new(),
+            severity: "low".to_string(),
+            start: Some(start),
+            end: Some(end),
+            help: None,
+            suggestions: Vec::new(),
+            fixable: false,
+            range: None,
+        };
+        // The second finding is nowhere near a site and must not claim one
+        let site = query.find("author").unwrap();
+        let mut findings = vec![finding(site, query.len() - 2), finding(0, 1)];
+        attach_fixes(query, &root, &mut findings);
+        assert_eq!(findings[0].suggestions.len(), 1);
+        assert!(findings[0].fixable);
+        assert!(findings[1].help.is_some());
+        assert!(findings[1].suggestions.is_empty());
+        assert!(!findings[1].fixable);
+    }
+
+    #[test]
+    fn test_lint_positions() {
+        let query = "*[title == \"日本\"]{ \"a\": author->_id }";
+        let config = LintConfig::from_json(Some(r#"{"positions": true}"#)).unwrap();
+        let findings = lint_findings(query, &config).unwrap();
+        let range = findings[0].range.unwrap();
+        assert_eq!(Some(range.start.utf8), findings[0].start);
+        assert_eq!(
+            range.start.utf16,
+            query[..findings[0].start.unwrap()].encode_utf16().count()
+        );
+        assert_eq!(range.start.line, 1);
+        assert_eq!(range.start.column, range.start.utf16 + 1);
+        assert!(findings[0].suggestions[0].range.is_some());
+
+        let findings = lint_findings(query, &LintConfig::default()).unwrap();
+        assert!(findings[0].range.is_none());
+    }
+
+    #[test]
+    fn test_fix_applies_machine_applicable_suggestions() {
+        let query = "*[_id match \"drafts.*\"]{ \"a\": author->_id, \"b\": editor->_id }";
+        let result = fix_query(query, &LintConfig::default()).unwrap();
+        // match-on-id's rewrite can change results, so it is only suggested
+        assert_eq!(
+            result.query,
+            "*[_id match \"drafts.*\"]{ \"a\": author._ref, \"b\": editor._ref }"
+        );
+        assert_eq!(result.fixes.len(), 2);
+        assert!(result.converged);
+
+        let config =
+            LintConfig::from_json(Some(r#"{"rules": {"join-to-get-id": false}}"#)).unwrap();
+        let result = fix_query(query, &config).unwrap();
+        assert_eq!(result.query, query);
+        assert!(result.fixes.is_empty());
+
+        let findings = lint_findings(query, &LintConfig::default()).unwrap();
+        let suggestion = &findings
+            .iter()
+            .find(|f| f.rule_id == "match_on_id")
+            .unwrap()
+            .suggestions[0];
+        assert_eq!(
+            suggestion.applicability,
+            fixes::Applicability::MaybeIncorrect
+        );
+    }
+
+    #[test]
+    fn test_lint_params() {
+        let config = LintConfig::from_json(Some(
+            r#"{"params": {"slug": "a", "limit": "10"}, "rules": {"unused_param": false}}"#,
+        ))
+        .unwrap();
+        let query = "*[slug.current == $slug && _type == $type][0...$limit]";
+        let findings = lint_findings(query, &config).unwrap();
+        let rule_ids: Vec<&str> = findings.iter().map(|f| f.rule_id.as_str()).collect();
+        assert_eq!(rule_ids, vec!["missing_param", "invalid_param_type"]);
+
+        // Without params the param rules don't run
+        let findings = lint_findings(query, &LintConfig::default()).unwrap();
+        assert!(findings.iter().all(|f| !f.rule_id.contains("param")));
+
+        // Supplied offsets are checked by value instead of flagged outright
+        let query = "*[_type == \"post\"][$offset...$end]";
+        let findings = lint_findings(query, &LintConfig::default()).unwrap();
+        assert_eq!(findings[0].rule_id, "deep_pagination_param");
+        let config =
+            LintConfig::from_json(Some(r#"{"params": {"offset": 2000, "end": 2010}}"#)).unwrap();
+        let findings = lint_findings(query, &config).unwrap();
+        let rule_ids: Vec<&str> = findings.iter().map(|f| f.rule_id.as_str()).collect();
+        assert_eq!(rule_ids, vec!["deep_pagination_param_value"]);
+    }
+
+    #[test]
+    fn test_lint_stats() {
+        let dataset = Dataset::from_ndjson(
+            "{\"_id\": \"a\", \"_type\": \"post\", \"title\": \"A\"}\n{\"_id\": \"b\", \"_type\": \"post\"}",
+        )
+        .unwrap();
+        let stats = serde_json::to_string(&DatasetStats::from_dataset(&dataset)).unwrap();
+        let config = LintConfig::from_json(Some(&format!(
+            r#"{{"stats": {}, "rules": {{"large-order": {{"threshold": 2}}}}}}"#,
+            stats
+        )))
+        .unwrap();
+
+        let query = "*[_type == \"post\" && defined(slug)] | order(title)";
+        let findings = lint_findings(query, &config).unwrap();
+        let rule_ids: Vec<&str> = findings.iter().map(|f| f.rule_id.as_str()).collect();
+        assert_eq!(rule_ids, vec!["large_order", "unset_field_filter"]);
+    }
+
+    #[test]
+    fn test_lint_batch() {
+        let queries = serde_json::from_str(
+            r#"[
+                {"id": "a.ts:3", "query": "*[_type == \"post\"]{ \"id\": author->_id }"},
+                {"id": "b.ts:7", "query": "*[_type == \"post\""},
+                {"id": "c.ts:1", "query": "*[_type == \"post\"]"}
+            ]"#,
+        )
+        .unwrap();
+        let results = batch_findings(queries, |q| lint_findings(q, &LintConfig::default()));
+        let results = serde_json::to_value(results).unwrap();
+        assert_eq!(results["a.ts:3"]["findings"][0]["ruleId"], "join_to_get_id");
+        assert_eq!(results["b.ts:7"]["error"]["code"], "PARSE_ERROR");
+        assert_eq!(results["c.ts:1"]["findings"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_lint_with_schema() {
+        let schema = r#"[{"type": "document", "name": "post", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}}
+        }}]"#;
+        let findings = lint_findings_with_schema(
+            "*[_type == \"post\"]{ titel }",
+            &Schema::from_json(schema).unwrap(),
+            &LintConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule_id, "unknown_field");
+        assert_eq!(
+            findings[0].help.as_deref(),
+            Some("Did you mean: \"title\"?")
+        );
+    }
+
+    #[test]
+    fn test_schema_handle_update() {
+        let json = r#"[{"type": "document", "name": "post", "attributes": {}}]"#;
+        let mut handle = SchemaHandle::new(json).unwrap();
+        assert_eq!(handle.document_types(), vec!["post"]);
+        assert!(!handle.update(json).unwrap());
+
+        let json = r#"[{"type": "document", "name": "page", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}}
+        }}]"#;
+        assert!(handle.update(json).unwrap());
+        assert_eq!(handle.document_types(), vec!["page"]);
+        assert_eq!(handle.fields_for_type("page"), vec!["title"]);
+    }
+
     #[test]
     fn test_format_query() {
         let result = format("*[_type==\"post\"]{title}", Some(80));
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_format_keeps_comments() {
+        let query = "// Posts\n*[_type == \"post\"]{\n  title, // Heading\n  body\n}";
+        let result = format_with(query, DEFAULT_WIDTH).unwrap();
+        assert!(result.starts_with("// Posts\n"));
+        assert!(result.contains("title, // Heading\n"));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let a = fingerprint("*[_type == \"post\"]{title} // list", None).unwrap();
+        let b = fingerprint("*[ _type == \"post\" ] {\n  title\n}", None).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 16);
+
+        let c = fingerprint("*[_type == \"page\"]{title}", None).unwrap();
+        assert_ne!(a, c);
+        let options = Some(r#"{"literalPlaceholders": true}"#.to_string());
+        assert_eq!(
+            fingerprint("*[_type == \"page\"]{title}", options.clone()).unwrap(),
+            fingerprint("*[_type == \"post\"]{title}", options).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_typegen() {
+        let schema = Schema::from_json(r#"[{"type": "document", "name": "post"}]"#).unwrap();
+        let ts = typegen_with("count(*[_type == \"post\"])", &schema, None).unwrap();
+        assert_eq!(ts, "export type QueryResult = number;\n");
+
+        let err = typegen_with("*", &schema, Some("not a name")).unwrap_err();
+        assert_eq!(err.code(), "INVALID_CONFIG");
+    }
+
+    #[test]
+    fn test_validator() {
+        let schema = Schema::from_json(r#"[{"type": "document", "name": "post"}]"#).unwrap();
+        let options = ValidatorOptions::from_json(Some(r#"{"zod": true}"#)).unwrap();
+        let output = validator_with("count(*)", &schema, &options).unwrap();
+        assert_eq!(output.json_schema["type"], "number");
+        assert!(output
+            .zod
+            .unwrap()
+            .ends_with("export const QueryResultSchema = z.number();\n"));
+
+        assert!(ValidatorOptions::from_json(Some(r#"{"zods": true}"#)).is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let dataset = Dataset::from_ndjson(
+            "{\"_id\": \"a\", \"_type\": \"post\", \"slug\": \"hello\"}\n{\"_id\": \"b\", \"_type\": \"page\"}",
+        )
+        .unwrap();
+        let result = evaluate_with(
+            "*[slug == $slug]._id",
+            Some(r#"{"slug": "hello"}"#),
+            &dataset,
+        );
+        assert_eq!(result.unwrap(), serde_json::json!(["a"]));
+
+        let error = evaluate_with("*[slug == $slug]", None, &dataset).unwrap_err();
+        assert_eq!(error.code(), "EVALUATION_ERROR");
+        assert_eq!(error.start(), Some(10));
+    }
+
+    #[test]
+    fn test_run_query_tests() {
+        let spec = r#"{
+            "documents": [{"_id": "a", "_type": "post"}],
+            "tests": [{"query": "count(*)", "expect": 1}, {"query": "*[0]._id", "expect": "b"}]
+        }"#;
+        let report = serde_json::to_value(query_test_report(spec).unwrap()).unwrap();
+        assert_eq!(report["passed"], 1);
+        assert_eq!(report["results"][1]["differences"][0]["actual"], "a");
+
+        let error = query_test_report(r#"{"documents": [{"_type": "post"}], "tests": []}"#);
+        assert_eq!(error.unwrap_err().code(), "INVALID_DATASET");
+    }
+
+    #[test]
+    fn test_format_range() {
+        let query = "*[_type == \"post\"]{\n  title,\n  \"a\": author->{   name  }\n}";
+        let start = query.find("name").unwrap();
+        let edit = format_range_with(query, start, start, DEFAULT_WIDTH);
+        let edit = edit.unwrap();
+        assert_eq!(&query[edit.start..edit.end], "name");
+
+        let start = query.find("author").unwrap();
+        let end = start + "author->{".len();
+        let edit = format_range_with(query, start, end, DEFAULT_WIDTH).unwrap();
+        assert_eq!(&query[edit.start..edit.end], "author->{   name  }");
+        assert_eq!(edit.new_text, "author->{ name }");
+        assert_eq!(edit.range.start.line, 3);
+
+        let error = format_range_with(query, 5, 1000, DEFAULT_WIDTH);
+        assert_eq!(error.unwrap_err().code(), "INVALID_RANGE");
+        let error = format_range_with(query, 5, 1, DEFAULT_WIDTH);
+        assert_eq!(error.unwrap_err().code(), "INVALID_RANGE");
+    }
+
+    #[test]
+    fn test_format_range_utf16_offsets() {
+        // The selection is given as JavaScript string indices, which are
+        // behind byte offsets after the emoji
+        let query = "*[title == \"😀\"]{ a,   b }";
+        let js_index = |needle: &str| query[..query.find(needle).unwrap()].encode_utf16().count();
+        let js_slice = |start: usize, end: usize| {
+            String::from_utf16(&query.encode_utf16().collect::<Vec<_>>()[start..end]).unwrap()
+        };
+        let start = js_index("a,");
+        let edit = format_range_with(query, start, start, DEFAULT_WIDTH).unwrap();
+        assert_eq!(js_slice(edit.start, edit.end), "a");
+        assert_eq!(edit.start, start);
+
+        let start = js_index("{");
+        let edit = format_range_with(query, start, start + 1, DEFAULT_WIDTH).unwrap();
+        assert_eq!(js_slice(edit.start, edit.end), "{ a,   b }");
+        assert_eq!(edit.new_text, "{ a, b }");
+    }
+
+    #[test]
+    fn test_format_edits() {
+        let query = "*[_type == \"post\"]{title,body}";
+        let cursor = query.find("body").unwrap();
+        let result = format_edits_with(query, DEFAULT_WIDTH, &[cursor]).unwrap();
+
+        let mut applied = query.to_string();
+        for edit in result.edits.iter().rev() {
+            applied.replace_range(edit.start..edit.end, &edit.new_text);
+        }
+        assert_eq!(applied, result.formatted);
+        assert!(result.formatted[result.offsets[0]..].starts_with("body"));
+
+        // Offsets in and out are UTF-16
+        let query = "*[title == \"日本\"]{title,body}";
+        let cursor = query[..query.find("body").unwrap()].encode_utf16().count();
+        let result = format_edits_with(query, DEFAULT_WIDTH, &[cursor]).unwrap();
+        let body = result.formatted.find("body").unwrap();
+        assert_eq!(
+            result.offsets,
+            vec![result.formatted[..body].encode_utf16().count()]
+        );
+
+        let error = format_edits_with(query, DEFAULT_WIDTH, &[1000]);
+        assert_eq!(error.unwrap_err().code(), "INVALID_RANGE");
+    }
+
+    #[test]
+    fn test_check_format() {
+        let query = "*[_type ==   \"post\"]";
+        let check = check_format_with(query, DEFAULT_WIDTH).unwrap();
+        assert!(!check.formatted);
+        let first = check.first_difference.unwrap();
+        assert_eq!(&query[first.start..first.end], "   ");
+        assert_eq!(first.new_text, " ");
+        assert!(check
+            .diff
+            .unwrap()
+            .starts_with("--- input\n+++ formatted\n@@"));
+
+        let formatted = format_with(query, DEFAULT_WIDTH).unwrap();
+        let check = check_format_with(&formatted, DEFAULT_WIDTH).unwrap();
+        assert!(check.formatted);
+        assert!(check.diff.is_none());
+
+        // A difference in line endings alone still shows in the diff
+        for ending in ["\n", "\r\n"] {
+            let query = format!("{}{}", formatted, ending);
+            let check = check_format_with(&query, DEFAULT_WIDTH).unwrap();
+            assert!(!check.formatted);
+            assert!(check.diff.unwrap().contains("@@"));
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("*[_type == \"post\"]").is_ok());
+        let error = validate("*[_type == \"post\"").unwrap_err();
+        assert_eq!(error.code(), "PARSE_ERROR");
+    }
+
+    #[test]
+    fn test_parse_returns_versioned_tree() {
+        let tree = syntax_tree("*[_type == \"post\"]{ title }").unwrap();
+        let tree = serde_json::to_value(tree).unwrap();
+        assert_eq!(tree["version"], 1);
+        assert_eq!(tree["root"]["type"], "Projection");
+        assert_eq!(tree["root"]["span"]["end"], 27);
+        assert_eq!(tree["root"]["base"]["type"], "Filter");
+        assert_eq!(tree["root"]["base"]["expr"]["op"], "==");
+        assert_eq!(
+            tree["root"]["expr"]["attributes"][0]["value"]["name"],
+            "title"
+        );
+
+        // The root is groq-parser's own serialization, untouched
+        let upstream = groq_parser::parse("*[_type == \"post\"]{ title }").unwrap();
+        assert_eq!(tree["root"], serde_json::to_value(upstream).unwrap());
+    }
+
+    #[test]
+    fn test_minify_keeps_the_tree() {
+        let minified = minify("*[ _type == \"post\" ] { title } // all").unwrap();
+        assert_eq!(minified, "*[_type==\"post\"]{title}");
+        assert_eq!(minify("*[").unwrap_err().code(), "PARSE_ERROR");
+    }
+
+    #[test]
+    fn test_parse_error_is_structured() {
+        let error = lint("*[_type == \"post\"", None).unwrap_err();
+        assert_eq!(error.code(), "PARSE_ERROR");
+        assert_eq!(error.start(), Some(17));
+
+        let error = format("*[_type == \"post\"", None).unwrap_err();
+        assert_eq!(error.code(), "PARSE_ERROR");
+
+        let error = lint("*", Some("{\"rules\": 1}".to_string())).unwrap_err();
+        assert_eq!(error.code(), "INVALID_CONFIG");
+    }
 }
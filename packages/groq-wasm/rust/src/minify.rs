@@ -0,0 +1,88 @@
+//! Shortest form of a query.
+//!
+//! Comments and whitespace are dropped, except for a single space between
+//! pieces that would otherwise read as one, such as `_id in`, `title desc`
+//! or the two `*` of `2 * *[0].n`. The significant pieces are kept exactly,
+//! so the query parses to the same tree; `shape` lets callers check that.
+
+use serde_json::Value;
+
+use crate::ast::Node;
+use crate::trivia::{scan, significant, Piece, PieceKind};
+
+/// Symbol pairs that form an operator when written together
+const OPERATORS: &[&str] = &[
+    "->", "==", "=>", "!=", "<=", ">=", "&&", "||", "**", "::", "..", "//",
+];
+
+pub fn minify(source: &str) -> String {
+    let pieces = significant(&scan(source));
+
+    let mut output = String::with_capacity(source.len());
+    let mut previous: Option<Piece> = None;
+    for piece in pieces {
+        let text = piece.text(source);
+        if let Some(previous) = previous {
+            let adjacent = previous.span.end == piece.span.start;
+            if !adjacent && !separate(previous.kind, previous.text(source), piece.kind, text) {
+                output.push(' ');
+            }
+        }
+        output.push_str(text);
+        previous = Some(piece);
+    }
+
+    output
+}
+
+/// A parse tree with its source spans removed, so trees of queries that
+/// only differ in layout compare equal
+pub fn shape(root: &Node) -> Value {
+    fn strip(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("span");
+                map.values_mut().for_each(strip);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(root).unwrap_or(Value::Null);
+    strip(&mut value);
+    value
+}
+
+/// Whether two pieces still read apart when written without a space
+fn separate(left_kind: PieceKind, left: &str, right_kind: PieceKind, right: &str) -> bool {
+    match (left_kind, right_kind) {
+        (PieceKind::Word, PieceKind::Word) => false,
+        (PieceKind::Symbol, PieceKind::Symbol) => {
+            let pair = format!("{}{}", left, right);
+            !OPERATORS.contains(&pair.as_str())
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    fn parsed_shape(query: &str) -> Value {
+        shape(&parse(query).unwrap())
+    }
+
+    #[test]
+    fn test_minify() {
+        let query = "// Posts\n*[_type == \"post\" && count(tags) > 0 && !(_id in path(\"drafts.**\"))] {\n  title, // Heading\n  \"n\": -  -1,\n  \"s\": tags[0 .. 2],\n  \"p\": 2 * *[0].n\n} | order(_createdAt desc)";
+        let minified = minify(query);
+        assert_eq!(
+            minified,
+            "*[_type==\"post\"&&count(tags)>0&&!(_id in path(\"drafts.**\"))]{title,\"n\":--1,\"s\":tags[0..2],\"p\":2* *[0].n}|order(_createdAt desc)"
+        );
+        assert_eq!(parsed_shape(&minified), parsed_shape(query));
+        assert_ne!(parsed_shape("*[a > 1]"), parsed_shape("*[a > -1]"));
+    }
+}
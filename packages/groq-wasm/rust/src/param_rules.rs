@@ -0,0 +1,242 @@
+//! Rules that check a query against the params it will be sent with.
+//!
+//! These only run when the lint config has a `params` object, since without
+//! the values there is nothing to check against:
+//!
+//! - `missing_param`: a `$param` the query uses is not supplied
+//! - `unused_param`: a supplied param is not used by the query. No part of
+//!   the query is at fault, so it is reported without a span
+//! - `invalid_param_type`: a value can't work where it is used, such as a
+//!   string as a slice bound or a number on either side of `match`
+//! - `deep_pagination_param_value`: a slice offset param whose value is at
+//!   least the `deep_pagination` limit. It replaces groq-lint's own
+//!   `deep_pagination_param`, which flags any param offset whatever its
+//!   value, when params are given
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::ast::{Node, NodeKind, OpKind, Walk};
+use crate::config::Severity;
+use crate::positions::Span;
+use crate::schema_rules::{did_you_mean, similar_names};
+
+/// A finding produced by a param rule
+pub struct ParamFinding {
+    pub rule_id: &'static str,
+    pub message: String,
+    pub help: String,
+    pub severity: Severity,
+    /// `None` for findings about the query as a whole
+    pub span: Option<Span>,
+}
+
+/// What a param's value must be, given where it is used
+#[derive(Clone, Copy)]
+enum Expected {
+    Integer,
+    Pattern,
+}
+
+/// Check the params used by a query against the supplied `params`.
+/// `deep_pagination` is the smallest slice offset that is deep pagination.
+pub fn check(
+    root: &Node,
+    params: &BTreeMap<String, Value>,
+    deep_pagination: u64,
+) -> Vec<ParamFinding> {
+    let mut findings = Vec::new();
+    let mut used = BTreeSet::new();
+
+    root.walk(&mut |node| match &node.kind {
+        NodeKind::Parameter { name } => {
+            used.insert(name.as_str());
+            if !params.contains_key(name) {
+                findings.push(missing(name, node.span(), params));
+            }
+        }
+        NodeKind::Slice { left, right, .. } => {
+            for bound in [left, right] {
+                check_value(bound, Expected::Integer, params, &mut findings);
+            }
+            if let Some((name, Some(offset))) = param_value(left, params) {
+                if let Some(offset) = offset.as_u64().filter(|o| *o >= deep_pagination) {
+                    findings.push(deep_offset(name, offset, left.span()));
+                }
+            }
+        }
+        NodeKind::OpCall {
+            op: OpKind::Match,
+            left,
+            right,
+        } => {
+            for side in [left, right] {
+                check_value(side, Expected::Pattern, params, &mut findings);
+            }
+        }
+        _ => {}
+    });
+
+    for name in params.keys().filter(|name| !used.contains(name.as_str())) {
+        findings.push(ParamFinding {
+            rule_id: "unused_param",
+            message: format!("Param ${} is supplied but not used by the query", name),
+            help: "Remove it from the params, or check the query for a misspelled param"
+                .to_string(),
+            severity: Severity::Low,
+            span: None,
+        });
+    }
+
+    findings.sort_by_key(|f| f.span.map(|s| (s.start, s.end)));
+    findings
+}
+
+/// The name of a param node and its supplied value, if any
+fn param_value<'a>(
+    node: &'a Node,
+    params: &'a BTreeMap<String, Value>,
+) -> Option<(&'a str, Option<&'a Value>)> {
+    match &node.kind {
+        NodeKind::Parameter { name } => Some((name, params.get(name))),
+        _ => None,
+    }
+}
+
+fn check_value(
+    node: &Node,
+    expected: Expected,
+    params: &BTreeMap<String, Value>,
+    findings: &mut Vec<ParamFinding>,
+) {
+    let Some((name, Some(value))) = param_value(node, params) else {
+        return;
+    };
+
+    let (valid, usage, wanted) = match expected {
+        Expected::Integer => (
+            value.is_i64() || value.is_u64(),
+            "a slice bound",
+            "an integer",
+        ),
+        Expected::Pattern => (
+            value.is_string()
+                || value
+                    .as_array()
+                    .is_some_and(|items| items.iter().all(Value::is_string)),
+            "`match`",
+            "a string or an array of strings",
+        ),
+    };
+
+    if !valid {
+        findings.push(ParamFinding {
+            rule_id: "invalid_param_type",
+            message: format!(
+                "Param ${} is {} but is used in {}, which needs {}",
+                name,
+                describe(value),
+                usage,
+                wanted
+            ),
+            help: format!("Pass {} as ${}", wanted, name),
+            severity: Severity::High,
+            span: Some(node.span()),
+        });
+    }
+}
+
+fn missing(name: &str, span: Span, params: &BTreeMap<String, Value>) -> ParamFinding {
+    let supplied: Vec<&str> = params.keys().map(String::as_str).collect();
+
+    ParamFinding {
+        rule_id: "missing_param",
+        message: format!("Param ${} is used but not supplied", name),
+        help: did_you_mean(&similar_names(name, &supplied))
+            .unwrap_or_else(|| format!("Add \"{}\" to the params", name)),
+        severity: Severity::High,
+        span: Some(span),
+    }
+}
+
+fn deep_offset(name: &str, offset: u64, span: Span) -> ParamFinding {
+    ParamFinding {
+        rule_id: "deep_pagination_param_value",
+        message: format!(
+            "Slice offset ${} is {}, which is deep pagination. This is slow because all skipped documents must be sorted first.",
+            name, offset
+        ),
+        help: "Consider using cursor-based pagination with _id instead, or validate the parameter value."
+            .to_string(),
+        severity: Severity::Low,
+        span: Some(span),
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(n) if n.is_f64() => "a fractional number",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    fn rule_ids(query: &str, params: &str) -> Vec<&'static str> {
+        let params: BTreeMap<String, Value> = serde_json::from_str(params).unwrap();
+        check(&parse(query).unwrap(), &params, 1000)
+            .into_iter()
+            .map(|f| f.rule_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_param_rules() {
+        let query = "*[_type == \"post\" && slug.current == $slug][$offset...$end]";
+        assert!(rule_ids(query, r#"{"slug": "a", "offset": 0, "end": 10}"#).is_empty());
+        assert_eq!(
+            rule_ids(query, r#"{"slg": "a", "offset": 0, "end": 10}"#),
+            vec!["unused_param", "missing_param"]
+        );
+        assert_eq!(
+            rule_ids(query, r#"{"slug": "a", "offset": 2000, "end": "10"}"#),
+            vec!["deep_pagination_param_value", "invalid_param_type"]
+        );
+        let params: BTreeMap<String, Value> =
+            serde_json::from_str(r#"{"slug": "a", "offset": 2000, "end": 2010}"#).unwrap();
+        assert!(check(&parse(query).unwrap(), &params, 5000).is_empty());
+
+        let query = "*[title match $terms]";
+        assert!(rule_ids(query, r#"{"terms": ["a*", "b"]}"#).is_empty());
+        assert_eq!(
+            rule_ids(query, r#"{"terms": 5}"#),
+            vec!["invalid_param_type"]
+        );
+    }
+
+    #[test]
+    fn test_unused_param_is_query_level() {
+        let query = "*[_type == \"post\"]";
+        let params = BTreeMap::from([("slug".to_string(), Value::from("a"))]);
+        let findings = check(&parse(query).unwrap(), &params, 1000);
+        assert_eq!(findings[0].rule_id, "unused_param");
+        assert_eq!(findings[0].span, None);
+    }
+
+    #[test]
+    fn test_missing_param_suggests_similar() {
+        let query = "*[slug.current == $slug]";
+        let params = BTreeMap::from([("slg".to_string(), Value::from("a"))]);
+        let findings = check(&parse(query).unwrap(), &params, 1000);
+        assert_eq!(findings[1].help, "Did you mean: \"slg\"?");
+    }
+}
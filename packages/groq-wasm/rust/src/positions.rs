@@ -0,0 +1,158 @@
+//! Conversion of UTF-8 byte offsets to the positions editors use.
+//!
+//! Spans are byte offsets into the query, but JavaScript strings, the LSP
+//! and ESLint count UTF-16 code units. Converting on the JS side means
+//! re-scanning the query per finding and is easy to get wrong for non-ASCII
+//! text, so it is done here once per query.
+
+use serde::{Deserialize, Serialize};
+
+/// A byte range in the query source (UTF-8 offsets, end exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// A single position in every unit a consumer might want
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    /// UTF-8 byte offset
+    pub utf8: usize,
+    /// UTF-16 code unit offset, i.e. a JavaScript string index
+    pub utf16: usize,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column, in UTF-16 code units
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Line starts of a query, for repeated offset lookups
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte and UTF-16 offset of the start of each line
+    lines: Vec<(usize, usize)>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut lines = vec![(0, 0)];
+        let mut utf16 = 0;
+        for (i, c) in source.char_indices() {
+            utf16 += c.len_utf16();
+            if c == '\n' {
+                lines.push((i + 1, utf16));
+            }
+        }
+        LineIndex { source, lines }
+    }
+
+    /// Position of a byte offset. Offsets past the end are clamped and
+    /// offsets inside a multi-byte character snap to its start.
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.lines.partition_point(|(start, _)| *start <= offset) - 1;
+        let (line_start, line_utf16) = self.lines[line];
+        let column: usize = self.source[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+
+        Position {
+            utf8: offset,
+            utf16: line_utf16 + column,
+            line: line + 1,
+            column: column + 1,
+        }
+    }
+
+    /// Byte offset of a UTF-16 offset, or `None` if it is past the end or
+    /// falls between the two halves of a surrogate pair
+    pub fn utf8_offset(&self, utf16: usize) -> Option<usize> {
+        let line = self.lines.partition_point(|(_, start)| *start <= utf16) - 1;
+        let (mut offset, mut at) = self.lines[line];
+        for c in self.source[offset..].chars() {
+            if at >= utf16 {
+                break;
+            }
+            at += c.len_utf16();
+            offset += c.len_utf8();
+        }
+        (at == utf16).then_some(offset)
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_non_ascii() {
+        let query = "*[title == \"日本\"]\n{ \"😀\": x }";
+        let index = LineIndex::new(query);
+
+        let x = query.find('x').unwrap();
+        assert_eq!(
+            index.position(x),
+            Position {
+                utf8: x,
+                utf16: 25,
+                line: 2,
+                column: 9,
+            }
+        );
+
+        let close = query.find(']').unwrap();
+        assert_eq!(index.position(close).utf16, 15);
+        assert_eq!(index.position(close).column, 16);
+
+        // Inside the emoji: snaps back to its first byte
+        let emoji = query.find('😀').unwrap();
+        assert_eq!(index.position(emoji + 2), index.position(emoji));
+        assert_eq!(index.position(1000).utf8, query.len());
+    }
+
+    #[test]
+    fn test_utf8_offset() {
+        let query = "*[title == \"日本\"]\n{ \"😀\": x }";
+        let index = LineIndex::new(query);
+        for (offset, _) in query.char_indices() {
+            assert_eq!(
+                index.utf8_offset(index.position(offset).utf16),
+                Some(offset)
+            );
+        }
+        assert_eq!(
+            index.utf8_offset(query.encode_utf16().count()),
+            Some(query.len())
+        );
+
+        // Between the surrogates of the emoji, and past the end
+        let emoji = index.position(query.find('😀').unwrap()).utf16;
+        assert_eq!(index.utf8_offset(emoji + 1), None);
+        assert_eq!(index.utf8_offset(1000), None);
+    }
+}
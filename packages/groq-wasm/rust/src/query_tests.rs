@@ -0,0 +1,307 @@
+//! Query tests: fixture documents, queries and the results they must give.
+//!
+//! A spec is JSON or YAML (JSON is read as YAML, so either works):
+//!
+//! ```yaml
+//! documents:
+//!   - { _id: nav-main, _type: navigation, items: [{ title: Home }] }
+//! tests:
+//!   - name: main navigation
+//!     query: '*[_id == $id][0].items[].title'
+//!     params: { id: nav-main }
+//!     expect: [Home]
+//!   - name: first item
+//!     query: '*[_type == "navigation"][0]'
+//!     expectAt:
+//!       /items/0/title: Home
+//! ```
+//!
+//! `expect` compares the whole result. `expectAt` maps JSON pointers into
+//! the result to the values expected there, so a test can pin down the
+//! parts that matter without repeating the rest. A test needs at least
+//! one of the two. Every difference is reported with the JSON pointer it
+//! is at.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ast;
+use crate::dataset::Dataset;
+use crate::error::GroqError;
+use crate::evaluate::evaluate;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Spec {
+    #[serde(default)]
+    pub documents: Vec<Value>,
+    pub tests: Vec<TestCase>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TestCase {
+    pub name: Option<String>,
+    pub query: String,
+    #[serde(default)]
+    pub params: BTreeMap<String, Value>,
+    /// `Some(Value::Null)` for an explicit `expect: null`
+    #[serde(default, deserialize_with = "present")]
+    pub expect: Option<Value>,
+    #[serde(default)]
+    pub expect_at: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub passed: usize,
+    pub failed: usize,
+    pub results: Vec<TestResult>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestResult {
+    pub name: String,
+    pub passed: bool,
+    /// The query failed to parse or evaluate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<GroqError>,
+    pub differences: Vec<Difference>,
+}
+
+/// A value that differs from the expected one. A missing side means the
+/// key or element doesn't exist there.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Difference {
+    pub pointer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<Value>,
+}
+
+impl Spec {
+    /// Read a JSON or YAML spec
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let spec: Spec = serde_yaml::from_str(source).map_err(|e| e.to_string())?;
+        for (index, test) in spec.tests.iter().enumerate() {
+            if test.expect.is_none() && test.expect_at.is_empty() {
+                return Err(format!(
+                    "{} needs `expect` or `expectAt`",
+                    test.display_name(index)
+                ));
+            }
+        }
+        Ok(spec)
+    }
+
+    pub fn run(&self) -> Result<Report, String> {
+        let dataset = Dataset::from_documents(self.documents.clone())?;
+
+        let results: Vec<TestResult> = self
+            .tests
+            .iter()
+            .enumerate()
+            .map(|(index, test)| test.run(index, &dataset))
+            .collect();
+        let passed = results.iter().filter(|r| r.passed).count();
+        Ok(Report {
+            passed,
+            failed: results.len() - passed,
+            results,
+        })
+    }
+}
+
+impl TestCase {
+    fn display_name(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("test {}", index + 1))
+    }
+
+    fn run(&self, index: usize, dataset: &Dataset) -> TestResult {
+        let mut result = TestResult {
+            name: self.display_name(index),
+            passed: false,
+            error: None,
+            differences: Vec::new(),
+        };
+
+        let actual = ast::parse(&self.query)
+            .and_then(|root| evaluate(&root, &self.params, dataset).map_err(GroqError::from));
+        let actual = match actual {
+            Ok(actual) => actual,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+
+        if let Some(expected) = &self.expect {
+            diff(expected, &actual, String::new(), &mut result.differences);
+        }
+        for (pointer, expected) in &self.expect_at {
+            match actual.pointer(pointer) {
+                Some(value) => diff(expected, value, pointer.clone(), &mut result.differences),
+                None => result.differences.push(Difference {
+                    pointer: pointer.clone(),
+                    expected: Some(expected.clone()),
+                    actual: None,
+                }),
+            }
+        }
+
+        result.passed = result.differences.is_empty();
+        result
+    }
+}
+
+/// Record where `actual` differs from `expected`, descending into objects
+/// and arrays so each difference is as specific as possible
+fn diff(expected: &Value, actual: &Value, pointer: String, out: &mut Vec<Difference>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let pointer = format!("{}/{}", pointer, escape(key));
+                match actual.get(key) {
+                    Some(actual) => diff(value, actual, pointer, out),
+                    None => out.push(Difference {
+                        pointer,
+                        expected: Some(value.clone()),
+                        actual: None,
+                    }),
+                }
+            }
+            for (key, value) in actual {
+                if !expected.contains_key(key) {
+                    out.push(Difference {
+                        pointer: format!("{}/{}", pointer, escape(key)),
+                        expected: None,
+                        actual: Some(value.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            for index in 0..expected.len().max(actual.len()) {
+                let pointer = format!("{}/{}", pointer, index);
+                match (expected.get(index), actual.get(index)) {
+                    (Some(expected), Some(actual)) => diff(expected, actual, pointer, out),
+                    (expected, actual) => out.push(Difference {
+                        pointer,
+                        expected: expected.cloned(),
+                        actual: actual.cloned(),
+                    }),
+                }
+            }
+        }
+        (Value::Number(a), Value::Number(b)) if a.as_f64() == b.as_f64() => {}
+        _ if expected == actual => {}
+        _ => out.push(Difference {
+            pointer,
+            expected: Some(expected.clone()),
+            actual: Some(actual.clone()),
+        }),
+    }
+}
+
+/// Deserialize a field that is present, even if `null`, as `Some`
+fn present<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Escape a key for use in a JSON pointer
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SPEC: &str = r#"
+documents:
+  - { _id: nav-main, _type: navigation, items: [{ title: Home }, { title: About }] }
+  - { _id: home, _type: page, slug: /, seo: { title: Welcome } }
+tests:
+  - name: main navigation
+    query: '*[_id == $id][0].items[].title'
+    params: { id: nav-main }
+    expect: [Home, About]
+  - name: seo
+    query: '*[_type == "page"][0]{ slug, seo }'
+    expectAt:
+      /seo/title: Home
+      /seo/description: Our site
+  - query: '*[_type == "page"'
+    expect: []
+"#;
+
+    #[test]
+    fn test_run_spec() {
+        let report = Spec::parse(SPEC).unwrap().run().unwrap();
+        assert_eq!((report.passed, report.failed), (1, 2));
+
+        let seo = &report.results[1];
+        assert_eq!(seo.name, "seo");
+        assert_eq!(
+            seo.differences,
+            vec![
+                Difference {
+                    pointer: "/seo/description".to_string(),
+                    expected: Some(json!("Our site")),
+                    actual: None,
+                },
+                Difference {
+                    pointer: "/seo/title".to_string(),
+                    expected: Some(json!("Home")),
+                    actual: Some(json!("Welcome")),
+                },
+            ]
+        );
+
+        let broken = &report.results[2];
+        assert_eq!(broken.name, "test 3");
+        assert_eq!(broken.error.as_ref().unwrap().code(), "PARSE_ERROR");
+    }
+
+    #[test]
+    fn test_spec_needs_expectation() {
+        let error = Spec::parse(r#"{"tests": [{"name": "sitemap", "query": "*"}]}"#).unwrap_err();
+        assert_eq!(error, "sitemap needs `expect` or `expectAt`");
+    }
+
+    #[test]
+    fn test_expect_null() {
+        let spec = r#"{"tests": [{"query": "*[_id == \"none\"][0]", "expect": null}]}"#;
+        let spec = Spec::parse(spec).unwrap();
+        assert_eq!(spec.tests[0].expect, Some(Value::Null));
+        assert_eq!(spec.run().unwrap().passed, 1);
+
+        let report = Spec::parse("tests: [{ query: count(*), expect: ~ }]")
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(report.failed, 1);
+    }
+
+    #[test]
+    fn test_diff_arrays() {
+        let mut out = Vec::new();
+        diff(
+            &json!([1, {"a/b": 2}]),
+            &json!([1.0, {"a/b": 3}, 4]),
+            String::new(),
+            &mut out,
+        );
+        let pointers: Vec<&str> = out.iter().map(|d| d.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/1/a~1b", "/2"]);
+    }
+}
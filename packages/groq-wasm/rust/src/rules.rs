@@ -0,0 +1,396 @@
+//! Metadata for every rule the wrapper can report.
+//!
+//! groq-lint doesn't expose its rule list, so it is kept here alongside the
+//! native rules. The JS packages, docs and playground build their rule
+//! tables from `rules()` instead of keeping their own copies.
+
+use serde::Serialize;
+
+use crate::config::Severity;
+use crate::fixes;
+use crate::stats_rules;
+use crate::thresholds;
+
+/// Where groq-lint's rules, and the native rules with a TypeScript
+/// counterpart, are documented
+const DOCS_BASE_URL: &str =
+    "https://github.com/sanity-labs/sanity-lint/blob/main/packages/groq-lint/src/rules";
+
+/// Static description of a rule
+pub struct Rule {
+    pub id: &'static str,
+    /// `performance` or `correctness`
+    pub category: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+    /// Whether `DOCS_BASE_URL` has a page for the rule
+    pub documented: bool,
+    pub requires_schema: bool,
+    pub requires_params: bool,
+    pub requires_stats: bool,
+}
+
+/// groq-lint's rules, as documented by their TypeScript counterparts
+pub const GROQ_LINT_RULES: &[Rule] = &[
+    Rule {
+        id: "join_in_filter",
+        category: "performance",
+        severity: Severity::High,
+        description: "Avoid `->` inside filters. It prevents optimization.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "join_to_get_id",
+        category: "performance",
+        severity: Severity::Low,
+        description: "Avoid using `->` to retrieve `_id`.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "computed_value_in_filter",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "Avoid computed values in filters. Indices cannot be used.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "match_on_id",
+        category: "correctness",
+        severity: Severity::Low,
+        description: "`match` on `_id` may not work as expected.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "order_on_expr",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "Ordering on computed values is slow.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "deep_pagination",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "Deep pagination with large offsets is slow.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "deep_pagination_param",
+        category: "performance",
+        severity: Severity::Low,
+        description: "Slice offset uses a parameter which could cause deep pagination.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "large_pages",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "Fetching many results at once can be slow.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "non_literal_comparison",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "Comparisons between two non-literal fields are slow.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "repeated_dereference",
+        category: "performance",
+        severity: Severity::Low,
+        description: "Repeatedly resolving the same reference is inefficient.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "count_in_correlated_subquery",
+        category: "performance",
+        severity: Severity::Low,
+        description: "count() on correlated subquery can be slow.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "very_large_query",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "This query is very large and may execute slowly.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "extremely_large_query",
+        category: "performance",
+        severity: Severity::High,
+        description: "This query is extremely large and will likely execute very slowly.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "many_joins",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "This query uses many joins and may have poor performance.",
+        documented: true,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: false,
+    },
+];
+
+/// The wrapper's own rules
+pub const NATIVE_RULES: &[Rule] = &[
+    Rule {
+        id: "invalid_type_filter",
+        category: "correctness",
+        severity: Severity::High,
+        description: "Document type in filter does not exist in schema",
+        documented: true,
+        requires_schema: true,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "unknown_field",
+        category: "correctness",
+        severity: Severity::Medium,
+        description: "Field in projection does not exist in schema",
+        documented: true,
+        requires_schema: true,
+        requires_params: false,
+        requires_stats: false,
+    },
+    Rule {
+        id: "missing_param",
+        category: "correctness",
+        severity: Severity::High,
+        description: "Param used by the query is not supplied",
+        documented: false,
+        requires_schema: false,
+        requires_params: true,
+        requires_stats: false,
+    },
+    Rule {
+        id: "unused_param",
+        category: "correctness",
+        severity: Severity::Low,
+        description: "Supplied param is not used by the query",
+        documented: false,
+        requires_schema: false,
+        requires_params: true,
+        requires_stats: false,
+    },
+    Rule {
+        id: "invalid_param_type",
+        category: "correctness",
+        severity: Severity::High,
+        description: "Param value cannot work where the param is used",
+        documented: false,
+        requires_schema: false,
+        requires_params: true,
+        requires_stats: false,
+    },
+    Rule {
+        id: "deep_pagination_param_value",
+        category: "performance",
+        severity: Severity::Low,
+        description: "Slice offset param value is deep pagination.",
+        documented: false,
+        requires_schema: false,
+        requires_params: true,
+        requires_stats: false,
+    },
+    Rule {
+        id: "unset_field_filter",
+        category: "correctness",
+        severity: Severity::Medium,
+        description: "Filter reads a field no document of the filtered types has set",
+        documented: false,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: true,
+    },
+    Rule {
+        id: "large_order",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "order() sorts many documents.",
+        documented: false,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: true,
+    },
+    Rule {
+        id: "large_array_deref",
+        category: "performance",
+        severity: Severity::Medium,
+        description: "Dereferencing a field that holds many references is slow.",
+        documented: false,
+        requires_schema: false,
+        requires_params: false,
+        requires_stats: true,
+    },
+];
+
+/// A rule-specific configuration option
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleOption {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub default: u64,
+    pub description: &'static str,
+}
+
+/// Serialized form of a rule, as returned by `rules()`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleInfo {
+    pub id: String,
+    pub kebab_id: String,
+    pub category: String,
+    pub default_severity: Severity,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs_url: Option<String>,
+    /// Whether `fix` can resolve the rule's findings
+    pub fixable: bool,
+    pub requires_schema: bool,
+    /// Only reported when the lint config supplies `params`
+    pub requires_params: bool,
+    /// Only reported when the lint config supplies `stats`
+    pub requires_stats: bool,
+    /// Options beyond `enabled` and `severity`, which every rule accepts
+    pub options: Vec<RuleOption>,
+}
+
+/// groq-lint's rules followed by the native ones
+pub fn registry() -> Vec<RuleInfo> {
+    GROQ_LINT_RULES
+        .iter()
+        .chain(NATIVE_RULES)
+        .map(RuleInfo::from)
+        .collect()
+}
+
+impl From<&Rule> for RuleInfo {
+    fn from(rule: &Rule) -> Self {
+        let kebab_id = rule.id.replace('_', "-");
+
+        let options = thresholds::THRESHOLD_RULES
+            .iter()
+            .chain(stats_rules::STATS_THRESHOLDS)
+            .filter(|(id, _)| *id == rule.id)
+            .map(|(_, default)| RuleOption {
+                name: "threshold",
+                kind: "number",
+                default: *default,
+                description: threshold_description(rule.id),
+            })
+            .collect();
+
+        RuleInfo {
+            id: rule.id.to_string(),
+            docs_url: rule.documented.then(|| docs_url(&kebab_id)),
+            kebab_id,
+            category: rule.category.to_string(),
+            default_severity: rule.severity,
+            description: rule.description.to_string(),
+            fixable: fixes::is_fixable_rule(rule.id),
+            requires_schema: rule.requires_schema,
+            requires_params: rule.requires_params,
+            requires_stats: rule.requires_stats,
+            options,
+        }
+    }
+}
+
+fn docs_url(kebab_id: &str) -> String {
+    format!("{}/{}.ts", DOCS_BASE_URL, kebab_id)
+}
+
+fn threshold_description(rule_id: &str) -> &'static str {
+    match rule_id {
+        "deep_pagination" => "Smallest slice offset that is reported",
+        "many_joins" => "Largest number of joins (`->`) that is not reported",
+        "large_order" => "Smallest number of sorted documents that is reported",
+        "large_array_deref" => "Smallest number of references per document that is reported",
+        _ => "Rule-specific limit",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_covers_all_rules() {
+        let rules = registry();
+        for (id, _) in thresholds::THRESHOLD_RULES {
+            let rule = rules.iter().find(|r| r.id == *id).unwrap();
+            assert_eq!(rule.options.len(), 1);
+        }
+        for (id, _) in stats_rules::STATS_THRESHOLDS {
+            let rule = rules.iter().find(|r| r.id == *id).unwrap();
+            assert_eq!(rule.options.len(), 1);
+            assert!(rule.docs_url.is_none());
+        }
+
+        let mut ids: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), rules.len(), "rule ids must be unique");
+
+        let rule = rules.iter().find(|r| r.id == "join_to_get_id").unwrap();
+        assert_eq!(rule.kebab_id, "join-to-get-id");
+        assert!(rule.fixable);
+        assert!(rule
+            .docs_url
+            .as_ref()
+            .unwrap()
+            .ends_with("/join-to-get-id.ts"));
+        let rule = rules.iter().find(|r| r.id == "match_on_id").unwrap();
+        assert!(!rule.fixable);
+
+        let json = serde_json::to_value(&rules[0]).unwrap();
+        assert_eq!(json["defaultSeverity"], "high");
+        assert_eq!(json["category"], "performance");
+    }
+}
@@ -0,0 +1,264 @@
+//! Model of the `schema.json` produced by `sanity schema extract`.
+//!
+//! The file is an array of top-level entries. Documents list their fields
+//! under `attributes`; named types that fields refer to via `inline` nodes
+//! are `type` entries. On load the entries are indexed by document type so
+//! lookups during linting and completion don't rescan the file.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+/// Fields Content Lake adds to every document
+pub const BUILT_IN_FIELDS: &[&str] = &["_id", "_type", "_rev", "_createdAt", "_updatedAt"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Attribute {
+    pub value: TypeNode,
+    /// The field may be missing from a document
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// A type node, in the same tagged shape as groq-js' `TypeNode`.
+///
+/// Variants this crate doesn't use deserialize as `Other`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TypeNode {
+    String {
+        /// Set when the type is a single string, like `_type`
+        value: Option<String>,
+    },
+    Number {
+        value: Option<serde_json::Number>,
+    },
+    Boolean {
+        value: Option<bool>,
+    },
+    Null,
+    Object {
+        #[serde(default)]
+        attributes: BTreeMap<String, Attribute>,
+        #[serde(rename = "dereferencesTo")]
+        dereferences_to: Option<String>,
+    },
+    Array {
+        of: Box<TypeNode>,
+    },
+    Union {
+        of: Vec<TypeNode>,
+    },
+    Inline {
+        name: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SchemaEntry {
+    Document {
+        name: String,
+        #[serde(default)]
+        attributes: BTreeMap<String, Attribute>,
+    },
+    Type {
+        name: String,
+        value: TypeNode,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Indexed view of a single document type
+#[derive(Debug, Clone, Default)]
+struct DocumentType {
+    fields: BTreeSet<String>,
+    attributes: BTreeMap<String, Attribute>,
+    /// Document types each reference field can point at
+    references: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    documents: BTreeMap<String, DocumentType>,
+    named_types: BTreeMap<String, TypeNode>,
+}
+
+impl Schema {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let entries: Vec<SchemaEntry> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut named_types = BTreeMap::new();
+        let mut documents = Vec::new();
+        for entry in entries {
+            match entry {
+                SchemaEntry::Document { name, attributes } => documents.push((name, attributes)),
+                SchemaEntry::Type { name, value } => {
+                    named_types.insert(name, value);
+                }
+                SchemaEntry::Other => {}
+            }
+        }
+
+        let documents = documents
+            .into_iter()
+            .map(|(name, attributes)| {
+                let mut document = DocumentType::default();
+                for (field, attribute) in &attributes {
+                    let mut targets = BTreeSet::new();
+                    collect_references(
+                        &attribute.value,
+                        &named_types,
+                        &mut Vec::new(),
+                        &mut targets,
+                    );
+                    if !targets.is_empty() {
+                        document.references.insert(field.clone(), targets);
+                    }
+                    document.fields.insert(field.clone());
+                }
+                document.attributes = attributes;
+                (name, document)
+            })
+            .collect();
+
+        Ok(Schema {
+            documents,
+            named_types,
+        })
+    }
+
+    pub fn has_document(&self, name: &str) -> bool {
+        self.documents.contains_key(name)
+    }
+
+    /// Names of all document types, sorted
+    pub fn document_types(&self) -> Vec<&str> {
+        self.documents.keys().map(String::as_str).collect()
+    }
+
+    /// Field names declared on a document type, or `None` for unknown types
+    pub fn fields_for_type(&self, name: &str) -> Option<Vec<&str>> {
+        self.documents
+            .get(name)
+            .map(|document| document.fields.iter().map(String::as_str).collect())
+    }
+
+    /// Whether a document type declares a field (built-in fields always exist)
+    pub fn has_field(&self, type_name: &str, field: &str) -> bool {
+        BUILT_IN_FIELDS.contains(&field)
+            || self
+                .documents
+                .get(type_name)
+                .is_some_and(|document| document.fields.contains(field))
+    }
+
+    /// Declared fields of a document type with their types
+    pub fn attributes(&self, type_name: &str) -> Option<&BTreeMap<String, Attribute>> {
+        self.documents
+            .get(type_name)
+            .map(|document| &document.attributes)
+    }
+
+    /// A named type that `inline` nodes refer to
+    pub fn named_type(&self, name: &str) -> Option<&TypeNode> {
+        self.named_types.get(name)
+    }
+
+    /// Document types a reference field (or array of references) can point at
+    pub fn reference_targets(&self, type_name: &str, field: &str) -> Vec<&str> {
+        self.documents
+            .get(type_name)
+            .and_then(|document| document.references.get(field))
+            .map(|targets| targets.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Follow arrays, unions and named types down to `dereferencesTo` markers
+fn collect_references<'a>(
+    node: &'a TypeNode,
+    named_types: &'a BTreeMap<String, TypeNode>,
+    visiting: &mut Vec<&'a str>,
+    targets: &mut BTreeSet<String>,
+) {
+    match node {
+        TypeNode::Object {
+            dereferences_to: Some(target),
+            ..
+        } => {
+            targets.insert(target.clone());
+        }
+        TypeNode::Array { of } => collect_references(of, named_types, visiting, targets),
+        TypeNode::Union { of } => {
+            for node in of {
+                collect_references(node, named_types, visiting, targets);
+            }
+        }
+        TypeNode::Inline { name } => {
+            // Named types can be recursive (e.g. nested menus)
+            if visiting.contains(&name.as_str()) {
+                return;
+            }
+            if let Some(node) = named_types.get(name) {
+                visiting.push(name);
+                collect_references(node, named_types, visiting, targets);
+                visiting.pop();
+            }
+        }
+        TypeNode::String { .. }
+        | TypeNode::Number { .. }
+        | TypeNode::Boolean { .. }
+        | TypeNode::Null
+        | TypeNode::Object { .. }
+        | TypeNode::Other => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"[
+        {"type": "document", "name": "post", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}},
+            "author": {"type": "objectAttribute", "value": {"type": "object", "attributes": {
+                "_ref": {"type": "objectAttribute", "value": {"type": "string"}}
+            }, "dereferencesTo": "author"}, "optional": true},
+            "related": {"type": "objectAttribute", "value": {"type": "array", "of": {"type": "inline", "name": "relatedRef"}}}
+        }},
+        {"type": "document", "name": "author", "attributes": {}},
+        {"type": "document", "name": "page", "attributes": {}},
+        {"type": "type", "name": "relatedRef", "value": {"type": "union", "of": [
+            {"type": "object", "attributes": {}, "dereferencesTo": "post"},
+            {"type": "object", "attributes": {}, "dereferencesTo": "page"}
+        ]}}
+    ]"#;
+
+    #[test]
+    fn test_schema_from_json() {
+        let schema = Schema::from_json(SCHEMA).unwrap();
+        assert_eq!(schema.document_types(), vec!["author", "page", "post"]);
+        assert_eq!(
+            schema.fields_for_type("post"),
+            Some(vec!["author", "related", "title"])
+        );
+        assert!(schema.has_field("post", "_id"));
+        assert!(!schema.has_field("post", "slug"));
+        assert!(schema.fields_for_type("relatedRef").is_none());
+    }
+
+    #[test]
+    fn test_schema_reference_targets() {
+        let schema = Schema::from_json(SCHEMA).unwrap();
+        assert_eq!(schema.reference_targets("post", "author"), vec!["author"]);
+        assert_eq!(
+            schema.reference_targets("post", "related"),
+            vec!["page", "post"]
+        );
+        assert!(schema.reference_targets("post", "title").is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+//! Schema-aware rules: `invalid_type_filter` and `unknown_field`.
+//!
+//! These mirror the TypeScript rules in groq-lint but run on groq-parser's
+//! tree (see `ast`), so the hybrid linter no longer needs groq-js for them.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::ast::{Node, NodeKind, OpKind, Walk};
+use crate::config::Severity;
+use crate::positions::Span;
+use crate::schema::{Schema, BUILT_IN_FIELDS};
+
+/// A finding produced by a schema rule
+pub struct SchemaFinding {
+    pub rule_id: &'static str,
+    pub message: String,
+    pub help: String,
+    pub severity: Severity,
+    pub span: Span,
+}
+
+/// Run both schema rules over a parsed query
+pub fn check(root: &Node, schema: &Schema) -> Vec<SchemaFinding> {
+    let mut findings = Vec::new();
+
+    root.walk(&mut |node| match &node.kind {
+        NodeKind::OpCall { op, left, right } => {
+            for (name, span) in type_comparisons(*op, left, right) {
+                if !schema.has_document(name) {
+                    findings.push(invalid_type(name, span, schema));
+                }
+            }
+        }
+        NodeKind::Filter { expr, .. } => {
+            if let Some(types) = scope_types(node, schema) {
+                check_scope(expr, &types, schema, &mut findings);
+            }
+        }
+        NodeKind::Projection { base, expr } => {
+            if let Some(types) = scope_types(base, schema) {
+                check_scope(expr, &types, schema, &mut findings);
+            }
+        }
+        NodeKind::PipeFuncCall { base, args, .. } => {
+            if let Some(types) = scope_types(base, schema) {
+                for arg in args {
+                    check_scope(arg, &types, schema, &mut findings);
+                }
+            }
+        }
+        _ => {}
+    });
+
+    findings.sort_by_key(|f| (f.span.start, f.span.end));
+    findings
+}
+
+fn invalid_type(name: &str, span: Span, schema: &Schema) -> SchemaFinding {
+    let types = schema.document_types();
+    let similar = similar_names(name, &types);
+
+    SchemaFinding {
+        rule_id: "invalid_type_filter",
+        message: format!("Document type \"{}\" does not exist in schema", name),
+        help: did_you_mean(&similar)
+            .unwrap_or_else(|| format!("Available types: {}", preview(&types))),
+        severity: Severity::High,
+        span,
+    }
+}
+
+/// String literals compared against `_type` with `==` or `in`
+pub fn type_comparisons<'a>(op: OpKind, left: &'a Node, right: &'a Node) -> Vec<(&'a str, Span)> {
+    match op {
+        OpKind::Eq => {
+            if is_type_attribute(left) {
+                string_value(right).into_iter().collect()
+            } else if is_type_attribute(right) {
+                string_value(left).into_iter().collect()
+            } else {
+                Vec::new()
+            }
+        }
+        OpKind::In if is_type_attribute(left) => match &right.kind {
+            NodeKind::Array { elements } => elements
+                .iter()
+                .filter(|e| !e.splat)
+                .filter_map(|e| string_value(&e.value))
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn is_type_attribute(node: &Node) -> bool {
+    matches!(&node.kind, NodeKind::AccessAttribute { base: None, name } if name == "_type")
+}
+
+fn string_value(node: &Node) -> Option<(&str, Span)> {
+    match &node.kind {
+        NodeKind::Value {
+            value: Value::String(s),
+        } => Some((s, node.span())),
+        _ => None,
+    }
+}
+
+/// Document types a filter restricts to, if all of them exist in the schema
+pub fn filter_types(expr: &Node, schema: &Schema) -> Option<BTreeSet<String>> {
+    filtered_types(expr).filter(|types| types.iter().all(|t| schema.has_document(t)))
+}
+
+/// Document types a filter restricts to with `_type == ...` or `_type in [...]`
+pub fn filtered_types(expr: &Node) -> Option<BTreeSet<String>> {
+    match &expr.kind {
+        NodeKind::OpCall { op, left, right } => {
+            let names = type_comparisons(*op, left, right);
+            if names.is_empty() {
+                return None;
+            }
+            Some(
+                names
+                    .into_iter()
+                    .map(|(name, _)| name.to_string())
+                    .collect(),
+            )
+        }
+        NodeKind::And { left, right } => filtered_types(left).or_else(|| filtered_types(right)),
+        NodeKind::Or { left, right } => {
+            let mut types = filtered_types(left)?;
+            types.extend(filtered_types(right)?);
+            Some(types)
+        }
+        NodeKind::Group { base } => filtered_types(base),
+        _ => None,
+    }
+}
+
+/// Document types flowing out of a traversal such as `*[_type == "post"][0...10]`
+fn scope_types(node: &Node, schema: &Schema) -> Option<BTreeSet<String>> {
+    match &node.kind {
+        NodeKind::Filter { base, expr } => scope_types(base, schema).or_else(|| {
+            matches!(base.kind, NodeKind::Everything)
+                .then(|| filter_types(expr, schema))
+                .flatten()
+        }),
+        NodeKind::Slice { base, .. }
+        | NodeKind::AccessElement { base, .. }
+        | NodeKind::ArrayCoerce { base }
+        | NodeKind::PipeFuncCall { base, .. } => scope_types(base, schema),
+        _ => None,
+    }
+}
+
+/// Check attribute reads that resolve against the documents in scope.
+///
+/// Only the base of nested traversals is visited: their filters and
+/// projections evaluate in a different scope and are handled on their own.
+fn check_scope(
+    node: &Node,
+    types: &BTreeSet<String>,
+    schema: &Schema,
+    findings: &mut Vec<SchemaFinding>,
+) {
+    match &node.kind {
+        NodeKind::AccessAttribute { base: None, name } => {
+            if !types.iter().any(|t| schema.has_field(t, name)) {
+                findings.push(unknown_field(name, node.span(), types, schema));
+            }
+        }
+        NodeKind::AccessAttribute {
+            base: Some(base), ..
+        }
+        | NodeKind::Filter { base, .. }
+        | NodeKind::Projection { base, .. }
+        | NodeKind::PipeFuncCall { base, .. }
+        | NodeKind::Slice { base, .. }
+        | NodeKind::AccessElement { base, .. } => check_scope(base, types, schema, findings),
+        _ => {
+            for child in node.children() {
+                check_scope(child, types, schema, findings);
+            }
+        }
+    }
+}
+
+fn unknown_field(
+    name: &str,
+    span: Span,
+    types: &BTreeSet<String>,
+    schema: &Schema,
+) -> SchemaFinding {
+    let fields: BTreeSet<&str> = types
+        .iter()
+        .filter_map(|t| schema.fields_for_type(t))
+        .flatten()
+        .collect();
+    let declared: Vec<&str> = fields.iter().copied().collect();
+    let candidates: Vec<&str> = fields
+        .iter()
+        .copied()
+        .chain(BUILT_IN_FIELDS.iter().copied())
+        .collect();
+    let similar = similar_names(name, &candidates);
+
+    let type_list = types
+        .iter()
+        .map(|t| format!("\"{}\"", t))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    SchemaFinding {
+        rule_id: "unknown_field",
+        message: format!("Field \"{}\" does not exist on type {}", name, type_list),
+        help: did_you_mean(&similar)
+            .unwrap_or_else(|| format!("Available fields: {}", preview(&declared))),
+        severity: Severity::Medium,
+        span,
+    }
+}
+
+pub fn did_you_mean(similar: &[&str]) -> Option<String> {
+    if similar.is_empty() {
+        return None;
+    }
+    let quoted: Vec<String> = similar.iter().map(|s| format!("\"{}\"", s)).collect();
+    Some(format!("Did you mean: {}?", quoted.join(", ")))
+}
+
+fn preview(names: &[&str]) -> String {
+    let mut list = names.iter().take(5).copied().collect::<Vec<_>>().join(", ");
+    if names.len() > 5 {
+        list.push_str("...");
+    }
+    list
+}
+
+/// Up to three candidates within edit distance 3, closest first
+pub fn similar_names<'a>(name: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+    let name = name.to_lowercase();
+    let mut scored: Vec<(usize, &str)> = candidates
+        .iter()
+        .map(|c| (levenshtein(&name, &c.to_lowercase()), *c))
+        .filter(|(distance, _)| *distance > 0 && *distance <= 3)
+        .collect();
+    scored.sort_by_key(|(distance, _)| *distance);
+    scored.into_iter().take(3).map(|(_, c)| c).collect()
+}
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    const SCHEMA: &str = r#"[
+        {"type": "document", "name": "post", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}},
+            "author": {"type": "objectAttribute", "value": {"type": "object", "attributes": {}}}
+        }},
+        {"type": "document", "name": "author", "attributes": {
+            "name": {"type": "objectAttribute", "value": {"type": "string"}}
+        }}
+    ]"#;
+
+    fn run(query: &str) -> Vec<SchemaFinding> {
+        check(&parse(query).unwrap(), &Schema::from_json(SCHEMA).unwrap())
+    }
+
+    #[test]
+    fn test_invalid_type_filter() {
+        let query = r#"*[_type in ["post", "atuhor"]]"#;
+        let findings = run(query);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule_id, "invalid_type_filter");
+        assert_eq!(
+            &query[findings[0].span.start..findings[0].span.end],
+            "\"atuhor\""
+        );
+        assert_eq!(findings[0].help, "Did you mean: \"author\"?");
+    }
+
+    #[test]
+    fn test_unknown_field_in_filter_and_projection() {
+        let query = r#"*[_type == "post" && defined(titel)] | order(_createdAt desc) { title, "a": author->name, body }"#;
+        let findings = run(query);
+        let fields: Vec<&str> = findings
+            .iter()
+            .map(|f| &query[f.span.start..f.span.end])
+            .collect();
+        assert_eq!(fields, vec!["titel", "body"]);
+        assert_eq!(findings[0].help, "Did you mean: \"title\"?");
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+}
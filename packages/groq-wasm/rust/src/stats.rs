@@ -0,0 +1,227 @@
+//! Statistics about the documents in a dataset, for data-aware linting.
+//!
+//! Stats are computed once from an NDJSON export and passed back to `lint`
+//! as the `stats` config key, so they are (de)serializable as camelCase
+//! JSON:
+//!
+//! ```json
+//! {
+//!   "documents": 1200,
+//!   "types": {
+//!     "playlist": {
+//!       "count": 40,
+//!       "fields": {
+//!         "title": { "presence": 1.0 },
+//!         "tracks": {
+//!           "presence": 0.95,
+//!           "arrayLength": { "min": 0, "max": 5000, "mean": 310.5 },
+//!           "references": { "min": 0, "max": 5000, "mean": 310.5 }
+//!         }
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Fields are keyed by path: nested object fields are joined with `.`
+//! (`slug.current`), while arrays are described by their length and not
+//! descended into. `presence` is the share of documents where the field is
+//! set to something other than `null`. `references` is the number of
+//! references a field holds on the documents that have it, for fields that
+//! hold any.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::dataset::Dataset;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetStats {
+    pub documents: u64,
+    pub types: BTreeMap<String, TypeStats>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeStats {
+    pub count: u64,
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldStats {
+    pub presence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub array_length: Option<Distribution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Distribution>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl DatasetStats {
+    pub fn from_dataset(dataset: &Dataset) -> Self {
+        let mut collectors: BTreeMap<&str, TypeCollector> = BTreeMap::new();
+        for document in dataset.documents() {
+            let type_name = document
+                .get("_type")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let collector = collectors.entry(type_name).or_default();
+            collector.count += 1;
+            if let Value::Object(fields) = document {
+                collector.add_fields(fields, "");
+            }
+        }
+
+        DatasetStats {
+            documents: dataset.documents().len() as u64,
+            types: collectors
+                .into_iter()
+                .map(|(name, collector)| (name.to_string(), collector.finish()))
+                .collect(),
+        }
+    }
+
+    /// Documents of the given types, or of all types without any
+    pub fn count(&self, types: Option<&[String]>) -> u64 {
+        match types {
+            Some(types) => types
+                .iter()
+                .filter_map(|t| self.types.get(t))
+                .map(|t| t.count)
+                .sum(),
+            None => self.documents,
+        }
+    }
+
+    /// Document count and stats of a field for each of the given types
+    /// that has documents. A field never seen on a type has no stats.
+    pub fn field<'a>(&'a self, types: &[String], path: &str) -> Vec<(u64, Option<&'a FieldStats>)> {
+        types
+            .iter()
+            .filter_map(|t| self.types.get(t))
+            .filter(|stats| stats.count > 0)
+            .map(|stats| (stats.count, stats.fields.get(path)))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct TypeCollector {
+    count: u64,
+    fields: BTreeMap<String, FieldCollector>,
+}
+
+#[derive(Default)]
+struct FieldCollector {
+    present: u64,
+    array_lengths: Vec<u64>,
+    references: Vec<u64>,
+}
+
+impl TypeCollector {
+    fn add_fields(&mut self, fields: &Map<String, Value>, prefix: &str) {
+        for (name, value) in fields {
+            if value.is_null() {
+                continue;
+            }
+            let path = format!("{}{}", prefix, name);
+            let field = self.fields.entry(path.clone()).or_default();
+            field.present += 1;
+            if let Value::Array(items) = value {
+                field.array_lengths.push(items.len() as u64);
+            }
+            field.references.push(count_references(value));
+
+            // References are leaves: `author._ref` is not a useful path
+            if let Value::Object(nested) = value {
+                if !nested.contains_key("_ref") {
+                    self.add_fields(nested, &format!("{}.", path));
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> TypeStats {
+        let count = self.count;
+        TypeStats {
+            count,
+            fields: self
+                .fields
+                .into_iter()
+                .map(|(path, field)| {
+                    let references = (field.references.iter().any(|n| *n > 0))
+                        .then(|| distribution(&field.references));
+                    let stats = FieldStats {
+                        presence: field.present as f64 / count as f64,
+                        array_length: (!field.array_lengths.is_empty())
+                            .then(|| distribution(&field.array_lengths)),
+                        references,
+                    };
+                    (path, stats)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn count_references(value: &Value) -> u64 {
+    match value {
+        Value::Object(object) if object.contains_key("_ref") => 1,
+        Value::Object(object) => object.values().map(count_references).sum(),
+        Value::Array(items) => items.iter().map(count_references).sum(),
+        _ => 0,
+    }
+}
+
+fn distribution(values: &[u64]) -> Distribution {
+    Distribution {
+        min: values.iter().copied().min().unwrap_or(0),
+        max: values.iter().copied().max().unwrap_or(0),
+        mean: values.iter().sum::<u64>() as f64 / values.len().max(1) as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_from_dataset() {
+        let dataset = Dataset::from_ndjson(
+            r#"{"_id": "a", "_type": "post", "slug": {"current": "a"}, "tags": ["x", "y"], "author": {"_ref": "z"}}
+{"_id": "b", "_type": "post", "slug": null, "tags": []}
+{"_id": "z", "_type": "author"}"#,
+        )
+        .unwrap();
+        let stats = DatasetStats::from_dataset(&dataset);
+
+        assert_eq!(stats.documents, 3);
+        let post = &stats.types["post"];
+        assert_eq!(post.count, 2);
+        assert_eq!(post.fields["slug.current"].presence, 0.5);
+        assert_eq!(post.fields["_id"].presence, 1.0);
+        assert_eq!(
+            post.fields["tags"].array_length,
+            Some(Distribution {
+                min: 0,
+                max: 2,
+                mean: 1.0
+            })
+        );
+        assert_eq!(post.fields["author"].references.as_ref().unwrap().max, 1);
+        assert!(!post.fields.contains_key("author._ref"));
+        assert_eq!(stats.count(Some(&["post".to_string()])), 2);
+    }
+}
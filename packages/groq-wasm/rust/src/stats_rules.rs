@@ -0,0 +1,294 @@
+//! Rules that use dataset statistics to find actual hot spots.
+//!
+//! These only run when the lint config has a `stats` object (see `stats`):
+//!
+//! - `unset_field_filter`: a filter reads a field that no document of the
+//!   filtered types has set, so the filter can't match as intended
+//! - `large_order`: `order()` sorts at least `threshold` documents, counted
+//!   from the type filter or the whole dataset when there is none
+//! - `large_array_deref`: a projection dereferences a field that holds at
+//!   least `threshold` references on some document
+
+use crate::ast::{Node, NodeKind, Walk};
+use crate::config::Severity;
+use crate::positions::Span;
+use crate::schema_rules::filtered_types;
+use crate::stats::DatasetStats;
+
+/// Configurable limits of the stats rules, with their defaults
+pub const STATS_THRESHOLDS: &[(&str, u64)] =
+    &[("large_order", 100_000), ("large_array_deref", 1000)];
+
+/// A finding produced by a stats rule
+pub struct StatsFinding {
+    pub rule_id: &'static str,
+    pub message: String,
+    pub help: String,
+    pub severity: Severity,
+    pub span: Span,
+}
+
+pub fn default_threshold(rule_id: &str) -> Option<u64> {
+    STATS_THRESHOLDS
+        .iter()
+        .find(|(id, _)| *id == rule_id)
+        .map(|(_, default)| *default)
+}
+
+/// Check a query against dataset stats. `threshold` gives the limit for a
+/// rule id.
+pub fn check(
+    root: &Node,
+    stats: &DatasetStats,
+    threshold: &dyn Fn(&str) -> u64,
+) -> Vec<StatsFinding> {
+    let mut findings = Vec::new();
+
+    root.walk(&mut |node| match &node.kind {
+        NodeKind::Filter { expr, .. } => {
+            if let Some(types) = scope_types(node) {
+                unset_fields(expr, &types, stats, &mut findings);
+            }
+        }
+        NodeKind::PipeFuncCall { base, name, .. } if name == "order" => {
+            let Some(types) = traversal_types(base) else {
+                return;
+            };
+            let count = stats.count(types.as_deref());
+            if count >= threshold("large_order") {
+                findings.push(large_order(count, types.is_some(), node.span()));
+            }
+        }
+        NodeKind::Projection { base, expr } => {
+            if let Some(types) = scope_types(base) {
+                let limit = threshold("large_array_deref");
+                large_derefs(expr, &types, stats, limit, &mut findings);
+            }
+        }
+        _ => {}
+    });
+
+    findings.sort_by_key(|f| (f.span.start, f.span.end));
+    findings
+}
+
+/// For a traversal starting at `*`, the document types its filters
+/// restrict to (`None` inside when there is no type filter)
+fn traversal_types(node: &Node) -> Option<Option<Vec<String>>> {
+    match &node.kind {
+        NodeKind::Everything => Some(None),
+        NodeKind::Filter { base, expr } => {
+            let types = traversal_types(base)?;
+            Some(types.or_else(|| filtered_types(expr).map(|t| t.into_iter().collect())))
+        }
+        NodeKind::Slice { base, .. }
+        | NodeKind::AccessElement { base, .. }
+        | NodeKind::ArrayCoerce { base }
+        | NodeKind::PipeFuncCall { base, .. }
+        | NodeKind::Group { base } => traversal_types(base),
+        _ => None,
+    }
+}
+
+/// Document types in scope of a traversal, when a type filter names them
+fn scope_types(node: &Node) -> Option<Vec<String>> {
+    traversal_types(node).flatten()
+}
+
+fn unset_fields(
+    node: &Node,
+    types: &[String],
+    stats: &DatasetStats,
+    findings: &mut Vec<StatsFinding>,
+) {
+    if let Some(path) = field_path(node) {
+        let fields = stats.field(types, &path);
+        let unset = !fields.is_empty()
+            && fields
+                .iter()
+                .all(|(_, field)| field.is_none_or(|f| f.presence == 0.0));
+        if unset {
+            let count: u64 = fields.iter().map(|(count, _)| count).sum();
+            findings.push(StatsFinding {
+                rule_id: "unset_field_filter",
+                message: format!(
+                    "Field \"{}\" is not set on any of the {} {} documents",
+                    path,
+                    count,
+                    type_list(types)
+                ),
+                help: "Check the field name, or whether the content model still uses this field"
+                    .to_string(),
+                severity: Severity::Medium,
+                span: node.span(),
+            });
+        }
+        return;
+    }
+
+    // Nested traversals are checked against their own types
+    match &node.kind {
+        NodeKind::Filter { base, .. }
+        | NodeKind::Projection { base, .. }
+        | NodeKind::PipeFuncCall { base, .. }
+        | NodeKind::Slice { base, .. }
+        | NodeKind::AccessElement { base, .. }
+        | NodeKind::Deref { base }
+        | NodeKind::ArrayCoerce { base } => unset_fields(base, types, stats, findings),
+        _ => {
+            for child in node.children() {
+                unset_fields(child, types, stats, findings);
+            }
+        }
+    }
+}
+
+fn large_order(count: u64, type_filtered: bool, span: Span) -> StatsFinding {
+    let (message, help) = if type_filtered {
+        (
+            format!("order() sorts {} documents", count),
+            "Narrow the filter further, or order on fewer documents",
+        )
+    } else {
+        (
+            format!("order() sorts {} documents without a type filter", count),
+            "Add a _type filter so fewer documents are sorted",
+        )
+    };
+    StatsFinding {
+        rule_id: "large_order",
+        message,
+        help: help.to_string(),
+        severity: Severity::Medium,
+        span,
+    }
+}
+
+fn large_derefs(
+    node: &Node,
+    types: &[String],
+    stats: &DatasetStats,
+    limit: u64,
+    findings: &mut Vec<StatsFinding>,
+) {
+    if let NodeKind::Deref { base } = &node.kind {
+        if let Some(path) = field_path(strip_array(base)) {
+            let fan_out = stats
+                .field(types, &path)
+                .iter()
+                .filter_map(|(_, field)| field.and_then(|f| f.references.as_ref()))
+                .map(|references| references.max)
+                .max();
+            if let Some(fan_out) = fan_out.filter(|n| *n >= limit) {
+                findings.push(StatsFinding {
+                    rule_id: "large_array_deref",
+                    message: format!(
+                        "\"{}\" holds up to {} references on {} documents, and each is resolved",
+                        path,
+                        fan_out,
+                        type_list(types)
+                    ),
+                    help: "Slice the array before dereferencing, or fetch the referenced documents separately"
+                        .to_string(),
+                    severity: Severity::Medium,
+                    span: node.span(),
+                });
+            }
+        }
+    }
+
+    match &node.kind {
+        NodeKind::Filter { base, .. }
+        | NodeKind::Projection { base, .. }
+        | NodeKind::PipeFuncCall { base, .. } => large_derefs(base, types, stats, limit, findings),
+        _ => {
+            for child in node.children() {
+                large_derefs(child, types, stats, limit, findings);
+            }
+        }
+    }
+}
+
+fn strip_array(node: &Node) -> &Node {
+    match &node.kind {
+        NodeKind::ArrayCoerce { base } => base,
+        _ => node,
+    }
+}
+
+/// Dotted path of a plain field read in the current scope, like
+/// `slug.current`
+fn field_path(node: &Node) -> Option<String> {
+    match &node.kind {
+        NodeKind::AccessAttribute { base: None, name } => Some(name.clone()),
+        NodeKind::AccessAttribute {
+            base: Some(base),
+            name,
+        } => Some(format!("{}.{}", field_path(base)?, name)),
+        _ => None,
+    }
+}
+
+fn type_list(types: &[String]) -> String {
+    types
+        .iter()
+        .map(|t| format!("\"{}\"", t))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+    use crate::dataset::Dataset;
+
+    fn run(query: &str) -> Vec<(&'static str, String)> {
+        let mut ndjson = String::new();
+        for i in 0..3 {
+            ndjson.push_str(&format!(
+                "{{\"_id\": \"p{}\", \"_type\": \"post\", \"title\": \"T\"}}\n",
+                i
+            ));
+        }
+        let refs: Vec<String> = (0..5)
+            .map(|i| format!("{{\"_ref\": \"p{}\"}}", i))
+            .collect();
+        ndjson.push_str(&format!(
+            "{{\"_id\": \"l\", \"_type\": \"playlist\", \"tracks\": [{}]}}\n",
+            refs.join(", ")
+        ));
+        let stats = DatasetStats::from_dataset(&Dataset::from_ndjson(&ndjson).unwrap());
+
+        let threshold = |rule_id: &str| match rule_id {
+            "large_order" => 4,
+            _ => 5,
+        };
+        check(&parse(query).unwrap(), &stats, &threshold)
+            .into_iter()
+            .map(|f| (f.rule_id, query[f.span.start..f.span.end].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unset_field_filter() {
+        let findings = run(r#"*[_type == "post" && defined(titel) && title == "T"]"#);
+        assert_eq!(findings, vec![("unset_field_filter", "titel".to_string())]);
+    }
+
+    #[test]
+    fn test_large_order() {
+        let findings = run("*[defined(title)] | order(title)");
+        assert_eq!(findings[0].0, "large_order");
+        assert!(run(r#"*[_type == "post"] | order(title)"#).is_empty());
+    }
+
+    #[test]
+    fn test_large_array_deref() {
+        let findings = run(r#"*[_type == "playlist"]{ "tracks": tracks[]->{ title } }"#);
+        assert_eq!(
+            findings,
+            vec![("large_array_deref", "tracks[]->".to_string())]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Configurable limits for groq-lint's threshold rules.
+//!
+//! groq-lint hard-codes the limits of `deep_pagination` and `many_joins`.
+//! When a config sets a `threshold` for one of them, the wrapper drops
+//! groq-lint's findings for that rule and checks it here instead, on the
+//! same tree and with the same messages.
+
+use crate::ast::{Node, NodeKind, Walk};
+use crate::config::Severity;
+use crate::positions::Span;
+
+/// A finding produced by a threshold rule
+pub struct ThresholdFinding {
+    pub rule_id: &'static str,
+    pub message: String,
+    pub severity: Severity,
+    pub span: Span,
+}
+
+/// Rules whose limit can be configured, with groq-lint's default limit
+pub const THRESHOLD_RULES: &[(&str, u64)] = &[("deep_pagination", 1000), ("many_joins", 10)];
+
+pub fn default_threshold(rule_id: &str) -> Option<u64> {
+    THRESHOLD_RULES
+        .iter()
+        .find(|(id, _)| *id == rule_id)
+        .map(|(_, default)| *default)
+}
+
+/// Run a single threshold rule with the given limit
+pub fn check(rule_id: &str, threshold: u64, query: &str, root: &Node) -> Vec<ThresholdFinding> {
+    let mut findings = Vec::new();
+
+    match rule_id {
+        "deep_pagination" => root.walk(&mut |node| {
+            let NodeKind::Slice { left, right, .. } = &node.kind else {
+                return;
+            };
+            let Some(offset) = integer(left).filter(|o| *o >= threshold) else {
+                return;
+            };
+            findings.push(ThresholdFinding {
+                rule_id: "deep_pagination",
+                message: format!(
+                    "Slice offset of {} is deep pagination. This is slow because all skipped documents must be sorted first.",
+                    offset
+                ),
+                severity: Severity::Medium,
+                span: Span::new(left.span().start, right.span().end),
+            });
+        }),
+        "many_joins" => {
+            let mut joins = 0;
+            root.walk(&mut |node| {
+                if let NodeKind::Deref { .. } = node.kind {
+                    joins += 1;
+                }
+            });
+            if joins > threshold {
+                findings.push(ThresholdFinding {
+                    rule_id: "many_joins",
+                    message: format!(
+                        "This query uses {} joins (->), which may cause poor performance.",
+                        joins
+                    ),
+                    severity: Severity::Medium,
+                    span: Span::new(0, query.len()),
+                });
+            }
+        }
+        _ => {}
+    }
+
+    findings
+}
+
+fn integer(node: &Node) -> Option<u64> {
+    match &node.kind {
+        NodeKind::Value { value } => value.as_u64(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+
+    fn messages(rule_id: &str, threshold: u64, query: &str) -> Vec<String> {
+        check(rule_id, threshold, query, &parse(query).unwrap())
+            .into_iter()
+            .map(|f| f.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_threshold_rules() {
+        let query = "*[_type == \"post\"][500...520]";
+        assert!(messages("deep_pagination", 1000, query).is_empty());
+        assert_eq!(messages("deep_pagination", 200, query).len(), 1);
+
+        let query = "*[_type == \"post\"]{ author->, category->, editor-> }";
+        assert!(messages("many_joins", 3, query).is_empty());
+        assert_eq!(
+            messages("many_joins", 2, query),
+            vec!["This query uses 3 joins (->), which may cause poor performance."]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Splitting a query into the pieces text-level passes work on.
+//!
+//! Restoring comments, computing format edits and minifying operate on the
+//! query text rather than its tree. They need to know where whitespace,
+//! comments and string literals are, and where a word or a symbol ends;
+//! that is all this scanner knows. It has no grammar and accepts any
+//! input: whether a query is valid is decided by groq-parser alone.
+
+use crate::positions::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceKind {
+    Whitespace,
+    /// `// ...` up to the end of the line
+    Comment,
+    /// A string literal, or the rest of the input if it is unterminated
+    String,
+    /// A run of letters, digits, `_` and `$`
+    Word,
+    /// Any other single character
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Piece {
+    pub kind: PieceKind,
+    pub span: Span,
+}
+
+impl Piece {
+    pub fn text(self, source: &str) -> &str {
+        &source[self.span.start..self.span.end]
+    }
+
+    /// Whitespace and comments carry no meaning
+    pub fn is_trivia(self) -> bool {
+        matches!(self.kind, PieceKind::Whitespace | PieceKind::Comment)
+    }
+}
+
+/// Split a query into pieces that concatenate back to the source
+pub fn scan(source: &str) -> Vec<Piece> {
+    let bytes = source.as_bytes();
+    let mut pieces = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let kind = match bytes[pos] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                while pos < bytes.len() && matches!(bytes[pos], b' ' | b'\t' | b'\n' | b'\r') {
+                    pos += 1;
+                }
+                PieceKind::Whitespace
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+                PieceKind::Comment
+            }
+            quote @ (b'"' | b'\'') => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] != quote {
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos = (pos + 1).min(bytes.len());
+                PieceKind::String
+            }
+            b if is_word_byte(b) => {
+                while pos < bytes.len() && is_word_byte(bytes[pos]) {
+                    pos += 1;
+                }
+                PieceKind::Word
+            }
+            _ => {
+                pos += source[pos..].chars().next().map_or(1, char::len_utf8);
+                PieceKind::Symbol
+            }
+        };
+
+        pieces.push(Piece {
+            kind,
+            span: Span::new(start, pos),
+        });
+    }
+
+    pieces
+}
+
+/// Pieces with whitespace and comments removed
+pub fn significant(pieces: &[Piece]) -> Vec<Piece> {
+    pieces.iter().copied().filter(|p| !p.is_trivia()).collect()
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_roundtrips_source() {
+        let source = "*[_type == \"po\\\"st\"] // posts\n{ title, \"日本\": $n }[0...10] \"open";
+        let pieces = scan(source);
+        let rebuilt: String = pieces.iter().map(|p| p.text(source)).collect();
+        assert_eq!(rebuilt, source);
+
+        let texts: Vec<&str> = significant(&pieces)
+            .iter()
+            .map(|p| p.text(source))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "*",
+                "[",
+                "_type",
+                "=",
+                "=",
+                "\"po\\\"st\"",
+                "]",
+                "{",
+                "title",
+                ",",
+                "\"日本\"",
+                ":",
+                "$n",
+                "}",
+                "[",
+                "0",
+                ".",
+                ".",
+                ".",
+                "10",
+                "]",
+                "\"open"
+            ]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! TypeScript declarations for an inferred query result.
+//!
+//! The result becomes `export type <Name> = ...;`, followed by an
+//! `export interface` for each document type it includes. Document
+//! interfaces are named in PascalCase (`blogPost` becomes `BlogPost`).
+
+use crate::infer::{document_fields, Field, Type};
+use crate::schema::Schema;
+
+const INDENT: &str = "  ";
+
+/// Declarations for a query result of type `ty`, named `name`
+pub fn declarations(ty: &Type, name: &str, schema: &Schema) -> String {
+    let mut out = format!("export type {} = {};\n", name, render(ty, 0));
+
+    let mut documents = Vec::new();
+    ty.documents(&mut documents);
+    for document in documents {
+        let fields = document_fields(schema, &document);
+        out.push_str(&format!(
+            "\nexport interface {} {}\n",
+            interface_name(&document),
+            render_fields(&fields, 0)
+        ));
+    }
+    out
+}
+
+/// Whether `name` can be used as a TypeScript type name
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn render(ty: &Type, depth: usize) -> String {
+    match ty {
+        Type::Unknown => "unknown".to_string(),
+        Type::Null => "null".to_string(),
+        Type::Boolean => "boolean".to_string(),
+        Type::Number => "number".to_string(),
+        Type::String => "string".to_string(),
+        Type::Literal(value) => value.to_string(),
+        Type::Array(of) => format!("Array<{}>", render(of, depth)),
+        Type::Object(fields) => render_fields(fields, depth),
+        Type::Reference(_) => "{ _ref: string; _type: \"reference\"; _weak?: boolean }".to_string(),
+        Type::Document(name) => interface_name(name),
+        Type::Union(of) if of.is_empty() => "never".to_string(),
+        Type::Union(of) => of
+            .iter()
+            .map(|t| render(t, depth))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+fn render_fields(fields: &[Field], depth: usize) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+
+    let indent = INDENT.repeat(depth + 1);
+    let mut out = "{\n".to_string();
+    for field in fields {
+        let key = if is_identifier(&field.name) {
+            field.name.clone()
+        } else {
+            serde_json::Value::String(field.name.clone()).to_string()
+        };
+        let optional = if field.optional { "?" } else { "" };
+        out.push_str(&format!(
+            "{}{}{}: {};\n",
+            indent,
+            key,
+            optional,
+            render(&field.ty, depth + 1)
+        ));
+    }
+    out.push_str(&INDENT.repeat(depth));
+    out.push('}');
+    out
+}
+
+/// `blogPost` and `sanity.imageAsset` become `BlogPost` and
+/// `SanityImageAsset`
+pub fn interface_name(document: &str) -> String {
+    document
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+    use crate::infer::infer;
+
+    const SCHEMA: &str = r#"[
+        {"type": "document", "name": "blogPost", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}},
+            "author": {"type": "objectAttribute", "value": {"type": "object", "attributes": {}, "dereferencesTo": "author"}, "optional": true}
+        }},
+        {"type": "document", "name": "author", "attributes": {}}
+    ]"#;
+
+    #[test]
+    fn test_declarations() {
+        let schema = Schema::from_json(SCHEMA).unwrap();
+        let root =
+            parse(r#"*[_type == "blogPost"]{ title, "by-line": author->, "n": 1 }[0]"#).unwrap();
+        let ts = declarations(&infer(&root, &schema), "PostQuery", &schema);
+
+        assert_eq!(
+            ts,
+            r#"export type PostQuery = {
+  title: string;
+  "by-line"?: Author;
+  n: 1;
+} | null;
+
+export interface Author {
+  _id: string;
+  _type: "author";
+  _rev: string;
+  _createdAt: string;
+  _updatedAt: string;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_interface_name() {
+        assert_eq!(interface_name("blogPost"), "BlogPost");
+        assert_eq!(interface_name("sanity.imageAsset"), "SanityImageAsset");
+    }
+}
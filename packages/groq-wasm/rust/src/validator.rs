@@ -0,0 +1,270 @@
+//! Runtime validators for an inferred query result: a JSON Schema document
+//! and, on request, a Zod snippet.
+//!
+//! Both describe the same shape as the TypeScript from `typegen`: a key is
+//! required unless it is optional in the inferred type.
+//!
+//! Options are passed as JSON:
+//!
+//! ```json
+//! { "name": "PostQuery", "zod": true }
+//! ```
+//!
+//! `name` titles the schema (and names the Zod constant, `PostQuerySchema`);
+//! it defaults to `QueryResult`. Document types are emitted once, as
+//! `$defs` entries or Zod constants, and referred to by name.
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::infer::{document_fields, Field, Type};
+use crate::schema::Schema;
+use crate::typegen::interface_name;
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ValidatorOptions {
+    #[serde(default = "default_name")]
+    pub name: String,
+    #[serde(default)]
+    pub zod: bool,
+}
+
+fn default_name() -> String {
+    "QueryResult".to_string()
+}
+
+impl Default for ValidatorOptions {
+    fn default() -> Self {
+        ValidatorOptions {
+            name: default_name(),
+            zod: false,
+        }
+    }
+}
+
+impl ValidatorOptions {
+    /// Parse options from their JSON form. `None` yields the defaults.
+    pub fn from_json(json: Option<&str>) -> Result<Self, String> {
+        match json {
+            Some(json) => serde_json::from_str(json).map_err(|e| e.to_string()),
+            None => Ok(ValidatorOptions::default()),
+        }
+    }
+}
+
+/// A JSON Schema document for a query result of type `ty`
+pub fn json_schema(ty: &Type, name: &str, schema: &Schema) -> Value {
+    let mut root = Map::new();
+    root.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+    root.insert("title".to_string(), json!(name));
+    if let Value::Object(body) = schema_for(ty) {
+        root.extend(body);
+    }
+
+    let defs: Map<String, Value> = documents(ty)
+        .into_iter()
+        .map(|document| {
+            let fields = document_fields(schema, &document);
+            (interface_name(&document), object_schema(&fields))
+        })
+        .collect();
+    if !defs.is_empty() {
+        root.insert("$defs".to_string(), Value::Object(defs));
+    }
+    Value::Object(root)
+}
+
+fn schema_for(ty: &Type) -> Value {
+    match ty {
+        Type::Unknown => json!({}),
+        Type::Null => json!({ "type": "null" }),
+        Type::Boolean => json!({ "type": "boolean" }),
+        Type::Number => json!({ "type": "number" }),
+        Type::String => json!({ "type": "string" }),
+        Type::Literal(value) => json!({ "const": value }),
+        Type::Array(of) => json!({ "type": "array", "items": schema_for(of) }),
+        Type::Object(fields) => object_schema(fields),
+        Type::Reference(_) => json!({
+            "type": "object",
+            "properties": {
+                "_ref": { "type": "string" },
+                "_type": { "const": "reference" },
+                "_weak": { "type": "boolean" }
+            },
+            "required": ["_ref", "_type"]
+        }),
+        Type::Document(name) => json!({ "$ref": format!("#/$defs/{}", interface_name(name)) }),
+        Type::Union(of) if of.is_empty() => json!({ "not": {} }),
+        Type::Union(of) => json!({ "anyOf": of.iter().map(schema_for).collect::<Vec<_>>() }),
+    }
+}
+
+fn object_schema(fields: &[Field]) -> Value {
+    let properties: Map<String, Value> = fields
+        .iter()
+        .map(|field| (field.name.clone(), schema_for(&field.ty)))
+        .collect();
+    let required: Vec<&str> = fields
+        .iter()
+        .filter(|field| is_required(field))
+        .map(|field| field.name.as_str())
+        .collect();
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn is_required(field: &Field) -> bool {
+    !field.optional
+}
+
+fn documents(ty: &Type) -> Vec<String> {
+    let mut documents = Vec::new();
+    ty.documents(&mut documents);
+    documents
+}
+
+/// A Zod snippet declaring `<name>Schema` for a query result of type `ty`
+pub fn zod(ty: &Type, name: &str, schema: &Schema) -> String {
+    let mut out = "import { z } from \"zod\";\n".to_string();
+    for document in documents(ty) {
+        let fields = document_fields(schema, &document);
+        out.push_str(&format!(
+            "\nexport const {}Schema = {};\n",
+            interface_name(&document),
+            zod_object(&fields, 0)
+        ));
+    }
+    out.push_str(&format!(
+        "\nexport const {}Schema = {};\n",
+        name,
+        zod_for(ty, 0)
+    ));
+    out
+}
+
+fn zod_for(ty: &Type, depth: usize) -> String {
+    match ty {
+        Type::Unknown => "z.unknown()".to_string(),
+        Type::Null => "z.null()".to_string(),
+        Type::Boolean => "z.boolean()".to_string(),
+        Type::Number => "z.number()".to_string(),
+        Type::String => "z.string()".to_string(),
+        Type::Literal(value) => format!("z.literal({})", value),
+        Type::Array(of) => format!("z.array({})", zod_for(of, depth)),
+        Type::Object(fields) => zod_object(fields, depth),
+        Type::Reference(_) => {
+            "z.object({ _ref: z.string(), _type: z.literal(\"reference\"), _weak: z.boolean().optional() })"
+                .to_string()
+        }
+        // Document schemas are declared before the result's
+        Type::Document(name) => format!("{}Schema", interface_name(name)),
+        Type::Union(of) if of.is_empty() => "z.never()".to_string(),
+        Type::Union(of) => {
+            let members: Vec<&Type> = of.iter().filter(|t| **t != Type::Null).collect();
+            let base = match members.as_slice() {
+                [single] => zod_for(single, depth),
+                _ => format!(
+                    "z.union([{}])",
+                    members
+                        .iter()
+                        .map(|t| zod_for(t, depth))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            if members.len() < of.len() {
+                format!("{}.nullable()", base)
+            } else {
+                base
+            }
+        }
+    }
+}
+
+fn zod_object(fields: &[Field], depth: usize) -> String {
+    if fields.is_empty() {
+        return "z.object({})".to_string();
+    }
+
+    let indent = INDENT.repeat(depth + 1);
+    let mut out = "z.object({\n".to_string();
+    for field in fields {
+        let key = Value::String(field.name.clone()).to_string();
+        let optional = if is_required(field) {
+            ""
+        } else {
+            ".optional()"
+        };
+        out.push_str(&format!(
+            "{}{}: {}{},\n",
+            indent,
+            key,
+            zod_for(&field.ty, depth + 1),
+            optional
+        ));
+    }
+    out.push_str(&INDENT.repeat(depth));
+    out.push_str("})");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::parse;
+    use crate::infer::infer;
+
+    const SCHEMA: &str = r#"[
+        {"type": "document", "name": "post", "attributes": {
+            "title": {"type": "objectAttribute", "value": {"type": "string"}},
+            "author": {"type": "objectAttribute", "value": {"type": "object", "attributes": {}, "dereferencesTo": "author"}}
+        }},
+        {"type": "document", "name": "author", "attributes": {
+            "name": {"type": "objectAttribute", "value": {"type": "string"}, "optional": true}
+        }}
+    ]"#;
+
+    fn inferred(query: &str) -> (Type, Schema) {
+        let schema = Schema::from_json(SCHEMA).unwrap();
+        (infer(&parse(query).unwrap(), &schema), schema)
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let (ty, schema) = inferred(r#"*[_type == "post"]{ title, "author": author-> }"#);
+        let json = json_schema(&ty, "Posts", &schema);
+
+        assert_eq!(json["title"], "Posts");
+        assert_eq!(json["type"], "array");
+        let item = &json["items"];
+        assert_eq!(item["properties"]["title"], json!({ "type": "string" }));
+        assert_eq!(
+            item["properties"]["author"],
+            json!({ "$ref": "#/$defs/Author" })
+        );
+        // Null values are left out of responses, so `author` can be missing
+        assert_eq!(item["required"], json!(["title"]));
+        assert_eq!(
+            json["$defs"]["Author"]["properties"]["_type"],
+            json!({ "const": "author" })
+        );
+    }
+
+    #[test]
+    fn test_zod() {
+        let (ty, schema) = inferred(r#"*[_type == "author"][0]{ name, "n": 1 }"#);
+        assert_eq!(
+            zod(&ty, "AuthorQuery", &schema),
+            r#"import { z } from "zod";
+
+export const AuthorQuerySchema = z.object({
+  "name": z.string().optional(),
+  "n": z.literal(1),
+}).nullable();
+"#
+        );
+    }
+}
@@ -23,7 +23,8 @@ cd "$RUST_DIR"
 # username and directory structure in the published WASM binary.
 export RUSTFLAGS="--remap-path-prefix=$HOME/.cargo=.cargo --remap-path-prefix=$RUST_DIR=."
 
-wasm-pack build --target web --out-dir "$WASM_DIR" --release
+# --locked: build exactly the dependency versions in the committed Cargo.lock
+wasm-pack build --target web --out-dir "$WASM_DIR" --release -- --locked
 
 # Clean up unnecessary files
 rm -f "$WASM_DIR/.gitignore"
@@ -4,13 +4,31 @@ import {
   isInitialized,
   lint,
   lintAsync,
+  rules,
+  RULE_ID_MAP,
+  Schema,
+  lintWithSchema,
   format,
   formatAsync,
+  formatRange,
   isValidSyntax,
   WasmError,
   mapRuleId,
   mapSeverity,
   DEFAULT_WIDTH,
+  parse,
+  Dataset,
+  analyze,
+  datasetStats,
+  evaluate,
+  fingerprint,
+  fix,
+  formatEdits,
+  lintBatch,
+  runQueryTests,
+  typegen,
+  validator,
+  type WasmNode,
 } from '../index.js'
 
 describe('@sanity/groq-wasm', () => {
@@ -64,6 +82,16 @@ describe('@sanity/groq-wasm', () => {
       })
       expect(Array.isArray(findings)).toBe(true)
     })
+
+    it('should tell fixable findings from ones that need review', () => {
+      const [joinToGetId] = lint('*[_type == "post"]{ "authorId": author->_id }')
+      expect(joinToGetId.fixable).toBe(true)
+      expect(joinToGetId.suggestions?.[0].applicability).toBe('machineApplicable')
+
+      const [matchOnId] = lint('*[_id match "drafts.*"]')
+      expect(matchOnId.fixable).toBe(false)
+      expect(matchOnId.suggestions?.[0].applicability).toBe('maybeIncorrect')
+    })
   })
 
   describe('format', () => {
@@ -101,6 +129,25 @@ describe('@sanity/groq-wasm', () => {
     })
   })
 
+  describe('formatRange', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should only rewrite the subtree covering the selection', () => {
+      const query = '*[title == "😀"]{ a,   b }'
+      const start = query.indexOf('{')
+      const edit = formatRange(query, start, start + 1)
+      // String indices, not byte offsets
+      expect(query.slice(edit.start, edit.end)).toBe('{ a,   b }')
+      expect(edit.newText).toBe('{ a, b }')
+    })
+
+    it('should reject a selection past the end', () => {
+      expect(() => formatRange('*', 0, 10)).toThrow(WasmError)
+    })
+  })
+
   describe('isValidSyntax', () => {
     beforeAll(async () => {
       await initWasm()
@@ -114,6 +161,431 @@ describe('@sanity/groq-wasm', () => {
       // Empty is technically valid
       expect(isValidSyntax('')).toBe(true)
     })
+
+    it('should return false for invalid query', () => {
+      expect(isValidSyntax('*[_type == "post"')).toBe(false)
+    })
+  })
+
+  describe('Schema', () => {
+    const schemaJson = [
+      {
+        type: 'document',
+        name: 'post',
+        attributes: {
+          title: { type: 'objectAttribute', value: { type: 'string' } },
+        },
+      },
+    ]
+
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should index document types and fields', () => {
+      const schema = new Schema(schemaJson)
+      expect(schema.documentTypes()).toEqual(['post'])
+      expect(schema.fieldsForType('post')).toEqual(['title'])
+      expect(schema.fieldsForType('missing')).toEqual([])
+      schema.free()
+    })
+
+    it('should lint against the schema', () => {
+      const schema = new Schema(JSON.stringify(schemaJson))
+      const findings = schema.lint('*[_type == "pst"]')
+      expect(findings.map((f) => f.ruleId)).toContain('invalid-type-filter')
+      schema.free()
+    })
+
+    it('should only re-index changed schemas', () => {
+      const schema = new Schema(schemaJson)
+      expect(schema.update(schemaJson)).toBe(false)
+      expect(schema.update([...schemaJson, { type: 'document', name: 'author', attributes: {} }])).toBe(
+        true
+      )
+      expect(schema.documentTypes()).toEqual(['author', 'post'])
+      schema.free()
+    })
+
+    it('should reject an invalid schema', () => {
+      expect(() => new Schema('{')).toThrow(WasmError)
+    })
+  })
+
+  describe('lintWithSchema', () => {
+    const schemaJson = [{ type: 'document', name: 'post', attributes: {} }]
+
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should report unknown document types with a suggestion', () => {
+      const findings = lintWithSchema('*[_type == "pst"]', schemaJson)
+      const finding = findings.find((f) => f.ruleId === 'invalid-type-filter')
+      expect(finding?.help).toContain('post')
+    })
+
+    it('should accept a Schema handle', () => {
+      const schema = new Schema(schemaJson)
+      expect(lintWithSchema('*[_type == "post"]', schema)).toEqual([])
+      schema.free()
+    })
+
+    it('should throw a structured error for invalid queries', () => {
+      expect(() => lintWithSchema('*[', schemaJson)).toThrow(WasmError)
+    })
+  })
+
+  describe('parse', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should return a versioned syntax tree', () => {
+      const tree = parse('*[_type == "post"]{ title }')
+      expect(tree.version).toBe(1)
+      expect(tree.root.type).toBe('Projection')
+      expect(tree.root.span).toEqual({ start: 0, end: 27 })
+      expect((tree.root.base as WasmNode).type).toBe('Filter')
+    })
+
+    it('should throw a structured error for invalid queries', () => {
+      try {
+        parse('*[_type == "post"')
+        expect.fail('should have thrown')
+      } catch (error) {
+        expect(error).toBeInstanceOf(WasmError)
+        expect((error as WasmError).code).toBe('PARSE_ERROR')
+      }
+    })
+  })
+
+  describe('fix', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should apply machine-applicable fixes', () => {
+      const result = fix('*[_type == "post"]{ "authorId": author->_id }')
+      expect(result.query).toBe('*[_type == "post"]{ "authorId": author._ref }')
+      expect(result.fixes).toHaveLength(1)
+      expect(result.fixes[0].ruleId).toBe('join_to_get_id')
+      expect(result.converged).toBe(true)
+    })
+
+    it('should leave suggestions that may change results', () => {
+      const query = '*[_id match "drafts.*"]'
+      expect(fix(query)).toEqual({ query, fixes: [], converged: true })
+    })
+
+    it('should not fix disabled rules', () => {
+      const query = '*[_type == "post"]{ "authorId": author->_id }'
+      expect(fix(query, { rules: { 'join-to-get-id': false } }).query).toBe(query)
+    })
+  })
+
+  describe('lintBatch', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should key results by query id', () => {
+      const results = lintBatch([
+        { id: 'a.ts:3', query: '*[_type == "post"]{ "id": author->_id }' },
+        { id: 'b.ts:7', query: '*[_type == "post"' },
+        { id: 'c.ts:1', query: '*[_type == "post"]' },
+      ])
+
+      const a = results['a.ts:3']
+      expect('findings' in a && a.findings[0].ruleId).toBe('join-to-get-id')
+
+      const b = results['b.ts:7']
+      expect('error' in b && b.error).toBeInstanceOf(WasmError)
+      expect('error' in b && b.error.code).toBe('PARSE_ERROR')
+
+      expect(results['c.ts:1']).toEqual({ findings: [] })
+    })
+
+    it('should lint against a Schema', () => {
+      const schema = new Schema([{ type: 'document', name: 'post', attributes: {} }])
+      const results = schema.lintBatch([{ id: 'q', query: '*[_type == "pst"]' }])
+      const q = results['q']
+      expect('findings' in q && q.findings.map((f) => f.ruleId)).toContain('invalid-type-filter')
+      schema.free()
+    })
+  })
+
+  describe('formatEdits', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should return edits that produce the formatted query', () => {
+      const query = '*[_type == "post"]{title,body}'
+      const result = formatEdits(query)
+      expect(result.formatted).toBe(format(query))
+
+      let applied = query
+      for (const edit of [...result.edits].reverse()) {
+        applied = applied.slice(0, edit.start) + edit.newText + applied.slice(edit.end)
+      }
+      expect(applied).toBe(result.formatted)
+    })
+
+    it('should map offsets into the formatted query', () => {
+      const query = '*[title == "日本"]{title,body}'
+      const { formatted, offsets } = formatEdits(query, undefined, [query.indexOf('body')])
+      expect(formatted.slice(offsets[0]).startsWith('body')).toBe(true)
+    })
+
+    it('should reject offsets past the end of the query', () => {
+      expect(() => formatEdits('*', undefined, [10])).toThrow(WasmError)
+    })
+  })
+
+  describe('fingerprint', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should ignore whitespace, comments and redundant parentheses', () => {
+      const hash = fingerprint('*[_type == "post"]{title}')
+      expect(hash).toMatch(/^[0-9a-f]{16}$/)
+      expect(fingerprint('*[ (_type=="post") ] {\n  title // shown\n}')).toBe(hash)
+      expect(fingerprint('*[_type == "page"]{title}')).not.toBe(hash)
+    })
+
+    it('should ignore operand order and literals when asked', () => {
+      const options = { ignoreFilterOrder: true, literalPlaceholders: true }
+      expect(fingerprint('*[_type == "post" && defined(slug)]', options)).toBe(
+        fingerprint('*[defined(slug) && _type == "page"]', options)
+      )
+    })
+
+    it('should reject unknown options', () => {
+      expect(() => fingerprint('*', { sortKeys: true } as never)).toThrow(WasmError)
+    })
+  })
+
+  describe('analyze', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should report what a query depends on', () => {
+      const analysis = analyze(
+        '*[_type in ["post", "page"] && slug.current == $slug] | order(publishedAt desc) {\n  "author": author->name\n}'
+      )
+      expect(analysis.documentTypes).toEqual(['page', 'post'])
+      expect(analysis.fields.map((f) => [f.path, f.context])).toEqual([
+        ['_type', 'filter'],
+        ['slug.current', 'filter'],
+        ['publishedAt', 'order'],
+        ['author->name', 'projection'],
+      ])
+      expect(analysis.references.map((r) => r.name)).toEqual(['author'])
+      expect(analysis.parameters.map((p) => p.name)).toEqual(['slug'])
+      expect(analysis.functions).toEqual(['order'])
+    })
+
+    it('should throw a structured error for invalid queries', () => {
+      expect(() => analyze('*[')).toThrow(WasmError)
+    })
+  })
+
+  describe('typegen', () => {
+    const schemaJson = [
+      {
+        type: 'document',
+        name: 'post',
+        attributes: {
+          title: { type: 'objectAttribute', value: { type: 'string' } },
+        },
+      },
+    ]
+
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should generate the result type of a query', () => {
+      const ts = typegen('*[_type == "post"][0]{ title }', schemaJson, 'PostTitle')
+      expect(ts).toContain('export type PostTitle = {\n  title: string;\n} | null;')
+    })
+
+    it('should accept a Schema handle', () => {
+      const schema = new Schema(schemaJson)
+      expect(typegen('*[_type == "post"]{ title }', schema)).toContain('export type QueryResult')
+      schema.free()
+    })
+
+    it('should reject type names that are not identifiers', () => {
+      expect(() => typegen('*', schemaJson, 'not a name')).toThrow(WasmError)
+    })
+  })
+
+  describe('validator', () => {
+    const schemaJson = [
+      {
+        type: 'document',
+        name: 'post',
+        attributes: {
+          title: { type: 'objectAttribute', value: { type: 'string' } },
+        },
+      },
+    ]
+
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should build a JSON Schema for the result', () => {
+      const { jsonSchema, zod } = validator('*[_type == "post"]{ title }', schemaJson, {
+        name: 'Posts',
+      })
+      expect(jsonSchema.title).toBe('Posts')
+      expect(jsonSchema.type).toBe('array')
+      expect(zod).toBeUndefined()
+    })
+
+    it('should emit Zod when asked, from a Schema handle', () => {
+      const schema = new Schema(schemaJson)
+      const { zod } = validator('*[_type == "post"][0]{ title }', schema, { zod: true })
+      expect(zod).toContain('export const QueryResultSchema = z.object({')
+      schema.free()
+    })
+
+    it('should reject unknown options', () => {
+      expect(() => validator('*', schemaJson, { strict: true } as never)).toThrow(WasmError)
+    })
+  })
+
+  describe('evaluate', () => {
+    const ndjson = [
+      '{"_id": "a", "_type": "post", "slug": "hello"}',
+      '{"_id": "b", "_type": "page"}',
+    ].join('\n')
+
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should evaluate a query against NDJSON', () => {
+      expect(evaluate('*[slug == $slug]._id', ndjson, { slug: 'hello' })).toEqual(['a'])
+    })
+
+    it('should evaluate repeatedly against a Dataset', () => {
+      const dataset = new Dataset(ndjson)
+      expect(dataset.size).toBe(2)
+      expect(evaluate('count(*)', dataset)).toBe(2)
+      expect(dataset.evaluate('*[_type == "page"][0]._id')).toBe('b')
+      dataset.free()
+    })
+
+    it('should report missing params with a span', () => {
+      try {
+        evaluate('*[slug == $slug]', ndjson)
+        expect.fail('should have thrown')
+      } catch (error) {
+        expect((error as WasmError).code).toBe('EVALUATION_ERROR')
+        expect((error as WasmError).span?.start).toBe(10)
+      }
+    })
+
+    it('should reject invalid NDJSON', () => {
+      expect(() => new Dataset('{')).toThrow(WasmError)
+    })
+  })
+
+  describe('runQueryTests', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should report passing and failing tests', () => {
+      const report = runQueryTests({
+        documents: [{ _id: 'a', _type: 'post' }],
+        tests: [
+          { query: 'count(*)', expect: 1 },
+          { query: '*[0]._id', expect: 'b' },
+        ],
+      })
+      expect(report.passed).toBe(1)
+      expect(report.failed).toBe(1)
+      expect(report.results[1].differences[0]).toMatchObject({ expected: 'b', actual: 'a' })
+    })
+
+    it('should read YAML specs', () => {
+      const report = runQueryTests(
+        ['documents:', '  - { _id: a, _type: post }', 'tests:', '  - query: count(*)', '    expect: 1'].join(
+          '\n'
+        )
+      )
+      expect(report.passed).toBe(1)
+    })
+
+    it('should reject documents without an _id', () => {
+      expect(() => runQueryTests({ documents: [{ _type: 'post' }], tests: [] })).toThrow(WasmError)
+    })
+  })
+
+  describe('datasetStats', () => {
+    const ndjson = [
+      '{"_id": "a", "_type": "post", "tags": ["x", "y"]}',
+      '{"_id": "b", "_type": "post", "tags": []}',
+      '{"_id": "z", "_type": "author"}',
+    ].join('\n')
+
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should count documents and fields per type', () => {
+      const stats = datasetStats(ndjson)
+      expect(stats.documents).toBe(3)
+      expect(stats.types.post.count).toBe(2)
+      expect(stats.types.post.fields.tags.arrayLength).toEqual({ min: 0, max: 2, mean: 1 })
+    })
+
+    it('should match the stats of a Dataset', () => {
+      const dataset = new Dataset(ndjson)
+      expect(datasetStats(dataset)).toEqual(datasetStats(ndjson))
+      dataset.free()
+    })
+
+    it('should enable the stats rules when passed to lint', () => {
+      const stats = datasetStats(ndjson)
+      const findings = lint('*[_type == "post" && defined(slug)]', { stats })
+      expect(findings.map((f) => f.ruleId)).toContain('unset-field-filter')
+    })
+  })
+
+  describe('rules', () => {
+    beforeAll(async () => {
+      await initWasm()
+    })
+
+    it('should list groq-lint and native rules once each', () => {
+      const all = rules()
+      const ids = all.map((rule) => rule.id)
+      expect(new Set(ids).size).toBe(ids.length)
+      expect(ids).toContain('join_in_filter')
+      expect(ids).toContain('unknown_field')
+    })
+
+    it('should only link docs that exist', () => {
+      const missingParam = rules().find((rule) => rule.id === 'missing_param')
+      expect(missingParam?.docsUrl).toBeUndefined()
+    })
+
+    it('should list rule options', () => {
+      const deepPagination = rules().find((rule) => rule.id === 'deep_pagination')
+      expect(deepPagination?.options[0]?.name).toBe('threshold')
+    })
+
+    it('should fill RULE_ID_MAP on init', () => {
+      expect(RULE_ID_MAP['join_to_get_id']).toBe('join-to-get-id')
+    })
   })
 
   describe('type mappings', () => {
@@ -0,0 +1,193 @@
+/**
+ * GROQ evaluation via WASM
+ *
+ * Runs queries against documents from a dataset export (NDJSON, one
+ * document per line), e.g. to test queries against fixture data without a
+ * Content Lake.
+ */
+
+import { toWasmError, WasmError, type WasmDatasetStats, type WasmTestReport } from './types.js'
+import {
+  callDatasetStats,
+  callEvaluate,
+  callRunQueryTests,
+  createDatasetHandle,
+  isInitialized,
+  type WasmDatasetHandle,
+} from './wasm-loader.js'
+
+/**
+ * Documents loaded from NDJSON that can be queried repeatedly
+ *
+ * Parsing a large export dominates the cost of `evaluate()`, so keep one
+ * alive when running many queries against the same documents.
+ *
+ * @example
+ * ```typescript
+ * import { Dataset, initWasm } from '@sanity-labs/groq-wasm'
+ *
+ * await initWasm()
+ *
+ * const dataset = new Dataset(await readFile('production.ndjson', 'utf8'))
+ * dataset.evaluate('count(*[_type == $type])', { type: 'post' })
+ * // 42
+ * ```
+ */
+export class Dataset {
+  readonly #handle: WasmDatasetHandle
+
+  /**
+   * @param ndjson - Documents as NDJSON, one document per line
+   * @throws {WasmError} If WASM is not initialized or a line is not a JSON
+   *   object
+   */
+  constructor(ndjson: string) {
+    if (!isInitialized()) {
+      throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+    }
+
+    try {
+      this.#handle = createDatasetHandle(ndjson)
+    } catch (error) {
+      throw toWasmError(error, 'Dataset')
+    }
+  }
+
+  /** Number of documents in the dataset */
+  get size(): number {
+    return this.#handle.size()
+  }
+
+  /** Statistics of these documents, as `datasetStats()` computes them */
+  stats(): WasmDatasetStats {
+    try {
+      return this.#handle.stats()
+    } catch (error) {
+      throw toWasmError(error, 'Dataset stats')
+    }
+  }
+
+  /**
+   * Evaluate a query against these documents, as `evaluate()` does
+   *
+   * @param query - The GROQ query string
+   * @param params - Optional query params, without the `$`
+   */
+  evaluate(query: string, params?: Record<string, unknown>): unknown {
+    try {
+      return this.#handle.evaluate(query, params && JSON.stringify(params))
+    } catch (error) {
+      throw toWasmError(error, 'Evaluate')
+    }
+  }
+
+  /** Release the WASM memory held by the dataset */
+  free(): void {
+    this.#handle.free()
+  }
+}
+
+/**
+ * Evaluate a GROQ query against documents from a dataset export
+ *
+ * Supports filters, projections, `->`, `order()`, slices and the common
+ * functions; anything else fails with `EVALUATION_ERROR`.
+ *
+ * @param query - The GROQ query string
+ * @param dataset - A `Dataset`, or NDJSON with one document per line
+ * @param params - Optional query params, without the `$`
+ * @returns The query result
+ * @throws {WasmError} If WASM is not initialized, the query doesn't parse,
+ *   the dataset is invalid or evaluation fails
+ *
+ * @example
+ * ```typescript
+ * evaluate('*[slug == $slug]._id', ndjson, { slug: 'hello' })
+ * // ['a']
+ * ```
+ */
+export function evaluate(
+  query: string,
+  dataset: Dataset | string,
+  params?: Record<string, unknown>
+): unknown {
+  if (dataset instanceof Dataset) {
+    return dataset.evaluate(query, params)
+  }
+
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callEvaluate(query, params && JSON.stringify(params), dataset)
+  } catch (error) {
+    throw toWasmError(error, 'Evaluate')
+  }
+}
+
+/**
+ * Compute statistics of a dataset for data-aware linting
+ *
+ * Pass the result as `stats` in the lint config to enable the
+ * `unset-field-filter`, `large-order` and `large-array-deref` checks.
+ *
+ * @param dataset - A `Dataset`, or NDJSON with one document per line
+ * @returns Document counts per `_type`, and per field its presence ratio,
+ *   array lengths and reference fan-out
+ * @throws {WasmError} If WASM is not initialized or the dataset is invalid
+ *
+ * @example
+ * ```typescript
+ * const stats = datasetStats(await readFile('production.ndjson', 'utf8'))
+ * lint('*[_type == "post"] | order(title)', { stats })
+ * ```
+ */
+export function datasetStats(dataset: Dataset | string): WasmDatasetStats {
+  if (dataset instanceof Dataset) {
+    return dataset.stats()
+  }
+
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callDatasetStats(dataset)
+  } catch (error) {
+    throw toWasmError(error, 'Dataset stats')
+  }
+}
+
+/**
+ * Run query tests against fixture documents
+ *
+ * The spec lists documents and tests, each with a query, optional params
+ * and the expected result (`expect`) or values at JSON pointers in it
+ * (`expectAt`).
+ *
+ * @param spec - The spec, as JSON or YAML text or already parsed
+ * @returns Pass and fail counts, and for each test any error and the
+ *   differences from the expected result
+ * @throws {WasmError} If WASM is not initialized, or the spec or its
+ *   documents are invalid. Failing tests are reported, not thrown.
+ *
+ * @example
+ * ```typescript
+ * const report = runQueryTests(await readFile('queries.test.yaml', 'utf8'))
+ * if (report.failed > 0) {
+ *   process.exitCode = 1
+ * }
+ * ```
+ */
+export function runQueryTests(spec: unknown): WasmTestReport {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callRunQueryTests(typeof spec === 'string' ? spec : JSON.stringify(spec))
+  } catch (error) {
+    throw toWasmError(error, 'Query tests')
+  }
+}
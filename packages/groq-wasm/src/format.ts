@@ -4,8 +4,25 @@
  * Wraps the Rust groq-format library compiled to WASM.
  */
 
-import { WasmError, type WasmFormatConfig } from './types.js'
-import { callFormat, isInitialized } from './wasm-loader.js'
+import {
+  toWasmError,
+  WasmError,
+  type WasmFingerprintOptions,
+  type WasmFormatCheck,
+  type WasmFormatConfig,
+  type WasmFormatEdits,
+  type WasmTextEdit,
+} from './types.js'
+import {
+  callCheckFormat,
+  callFingerprint,
+  callFormat,
+  callFormatEdits,
+  callFormatRange,
+  callMinify,
+  callValidate,
+  isInitialized,
+} from './wasm-loader.js'
 
 /**
  * Default line width for formatting
@@ -46,13 +63,186 @@ export function format(query: string, config?: WasmFormatConfig): string {
   try {
     return callFormat(query, width)
   } catch (error) {
-    const message = error instanceof Error ? error.message : String(error)
+    throw toWasmError(error, 'Format')
+  }
+}
 
-    if (message.includes('parse') || message.includes('Parse')) {
-      throw new WasmError(`Failed to parse query: ${message}`, 'PARSE_ERROR')
-    }
+/**
+ * Format only the part of a query covering a selection
+ *
+ * The selection is widened to the smallest subtree that can be formatted
+ * on its own, such as a projection or a filter. Everything outside it is
+ * left as-is.
+ *
+ * @param query - The GROQ query string
+ * @param start - Start of the selection (a string index, i.e. UTF-16)
+ * @param end - End of the selection (a string index, i.e. UTF-16)
+ * @param config - Optional configuration (width defaults to 80)
+ * @returns A single edit, with `start`/`end` as string indices
+ * @throws {WasmError} If WASM is not initialized, the query doesn't parse
+ *   or the selection is out of bounds
+ *
+ * @example
+ * ```typescript
+ * const query = '*[_type == "post"]{ title,   body }'
+ * const edit = formatRange(query, 20, 20)
+ * const result = query.slice(0, edit.start) + edit.newText + query.slice(edit.end)
+ * ```
+ */
+export function formatRange(
+  query: string,
+  start: number,
+  end: number,
+  config?: WasmFormatConfig
+): WasmTextEdit {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  const width = config?.width ?? DEFAULT_WIDTH
+
+  try {
+    return callFormatRange(query, start, end, width)
+  } catch (error) {
+    throw toWasmError(error, 'Format')
+  }
+}
+
+/**
+ * Format a query and return the minimal edits that turn it into the
+ * formatted query
+ *
+ * Editors can apply the edits instead of replacing the whole document,
+ * which keeps undo history small and markers in untouched text in place.
+ *
+ * @param query - The GROQ query string to format
+ * @param config - Optional configuration (width defaults to 80)
+ * @param offsets - Offsets to carry over, such as the cursor and selection
+ *   anchors (string indices, i.e. UTF-16)
+ * @returns The formatted query, the edits in input order, and each offset
+ *   mapped into the formatted query. Edit `start`/`end` are string
+ *   indices.
+ * @throws {WasmError} If WASM is not initialized, the query doesn't parse
+ *   or an offset is out of bounds
+ *
+ * @example
+ * ```typescript
+ * const { formatted, offsets } = formatEdits(text, undefined, [cursor])
+ * editor.setValue(formatted)
+ * editor.setCursor(offsets[0])
+ * ```
+ */
+export function formatEdits(
+  query: string,
+  config?: WasmFormatConfig,
+  offsets: number[] = []
+): WasmFormatEdits {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  const width = config?.width ?? DEFAULT_WIDTH
 
-    throw new WasmError(`Format failed: ${message}`, 'WASM_ERROR')
+  try {
+    return callFormatEdits(query, width, offsets)
+  } catch (error) {
+    throw toWasmError(error, 'Format')
+  }
+}
+
+/**
+ * Minify a GROQ query for shipping in bundles or URLs
+ *
+ * Removes comments and every space that doesn't separate two tokens. The
+ * result parses to the same query.
+ *
+ * @param query - The GROQ query string to minify
+ * @returns The shortest form of the query
+ * @throws {WasmError} If WASM is not initialized or query parsing fails
+ *
+ * @example
+ * ```typescript
+ * minify('*[_type == "post"] {\n  title // shown in lists\n}')
+ * // '*[_type=="post"]{title}'
+ * ```
+ */
+export function minify(query: string): string {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callMinify(query)
+  } catch (error) {
+    throw toWasmError(error, 'Minify')
+  }
+}
+
+/**
+ * Fingerprint the structure of a GROQ query, e.g. to group the same query
+ * across files or in logs
+ *
+ * Queries that differ only in whitespace, comments or redundant parentheses
+ * share a fingerprint.
+ *
+ * @param query - The GROQ query string
+ * @param options - Optionally also ignore `&&`/`||` operand order and
+ *   literal values
+ * @returns A 64-bit hash of the normalized query, as 16 hex digits
+ * @throws {WasmError} If WASM is not initialized or query parsing fails
+ *
+ * @example
+ * ```typescript
+ * fingerprint('*[_type == "post"]') === fingerprint('*[ (_type=="post") ]')
+ * // true
+ * ```
+ */
+export function fingerprint(query: string, options?: WasmFingerprintOptions): string {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callFingerprint(query, JSON.stringify(options ?? {}))
+  } catch (error) {
+    throw toWasmError(error, 'Fingerprint')
+  }
+}
+
+/**
+ * Check whether a query is already formatted, without rewriting it
+ *
+ * @param query - The GROQ query string to check
+ * @param config - Optional configuration (width defaults to 80)
+ * @returns Whether the query is formatted; if not, the first differing span
+ *   and a unified diff against the formatted query
+ * @throws {WasmError} If WASM is not initialized or query parsing fails
+ *
+ * @example
+ * ```typescript
+ * const result = checkFormat('*[_type=="post"]')
+ * if (!result.formatted) {
+ *   console.error(result.diff)
+ *   process.exitCode = 1
+ * }
+ * ```
+ */
+export function checkFormat(query: string, config?: WasmFormatConfig): WasmFormatCheck {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  const width = config?.width ?? DEFAULT_WIDTH
+
+  // An empty query is left as-is by format()
+  if (!query.trim()) {
+    return { formatted: true }
+  }
+
+  try {
+    return callCheckFormat(query, width)
+  } catch (error) {
+    throw toWasmError(error, 'Format')
   }
 }
 
@@ -66,20 +256,30 @@ export async function formatAsync(query: string, config?: WasmFormatConfig): Pro
 }
 
 /**
- * Check if a query can be parsed (without full formatting)
+ * Check if a query can be parsed (without formatting it)
  *
  * @param query - The GROQ query to validate
  * @returns true if the query is valid GROQ syntax
  */
 export function isValidSyntax(query: string): boolean {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  // Empty is technically valid; format() leaves it as-is
+  if (!query.trim()) {
+    return true
+  }
+
   try {
-    format(query)
+    callValidate(query)
     return true
   } catch (error) {
-    if (error instanceof WasmError && error.code === 'PARSE_ERROR') {
+    const wasmError = toWasmError(error, 'Validate')
+    if (wasmError.code === 'PARSE_ERROR') {
       return false
     }
     // Re-throw other errors
-    throw error
+    throw wasmError
   }
 }
@@ -32,22 +32,72 @@
  */
 
 // Re-export from lint module
-export { lint, lintAsync } from './lint.js'
+export { fix, lint, lintAsync, lintBatch, rules } from './lint.js'
 
 // Re-export from format module
-export { DEFAULT_WIDTH, format, formatAsync, isValidSyntax } from './format.js'
+export {
+  checkFormat,
+  DEFAULT_WIDTH,
+  fingerprint,
+  format,
+  formatAsync,
+  formatEdits,
+  formatRange,
+  isValidSyntax,
+  minify,
+} from './format.js'
+
+// Re-export from dataset module
+export { Dataset, datasetStats, evaluate, runQueryTests } from './dataset.js'
+
+// Re-export from parse module
+export { analyze, parse } from './parse.js'
+
+// Re-export from schema module
+export { lintWithSchema, Schema, typegen, validator } from './schema.js'
 
 // Re-export from wasm-loader
 export { initWasm, isInitialized } from './wasm-loader.js'
 
 // Re-export types
 export {
+  type LintBatchResult,
   mapRuleId,
   mapSeverity,
   RULE_ID_MAP,
+  toWasmError,
   WasmError,
+  type WasmDatasetStats,
+  type WasmDistribution,
+  type WasmErrorCode,
+  type WasmAnalysis,
+  type WasmAppliedFix,
+  type WasmBatchQuery,
+  type WasmErrorDetails,
+  type WasmFieldRead,
+  type WasmFieldStats,
+  type WasmFingerprintOptions,
   type WasmFinding,
+  type WasmFixResult,
+  type WasmFormatCheck,
   type WasmFormatConfig,
+  type WasmFormatEdits,
+  type WasmGroqError,
   type WasmLintConfig,
+  type WasmNode,
+  type WasmOccurrence,
+  type WasmPosition,
+  type WasmRange,
+  type WasmRule,
+  type WasmRuleOption,
+  type WasmRuleSetting,
   type WasmSeverity,
+  type WasmSuggestion,
+  type WasmSyntaxTree,
+  type WasmTestDifference,
+  type WasmTestReport,
+  type WasmTestResult,
+  type WasmTextEdit,
+  type WasmValidator,
+  type WasmValidatorOptions,
 } from './types.js'
@@ -6,13 +6,21 @@
 
 import type { Finding, SourceLocation, SourceSpan } from '@sanity-labs/lint-core'
 import {
+  type LintBatchResult,
   mapRuleId,
   mapSeverity,
+  toWasmError,
   WasmError,
+  type WasmBatchQuery,
+  type WasmBatchResult,
   type WasmFinding,
+  type WasmFixResult,
   type WasmLintConfig,
+  type WasmPosition,
+  type WasmRange,
+  type WasmRule,
 } from './types.js'
-import { callLint, isInitialized } from './wasm-loader.js'
+import { callFix, callLint, callLintBatch, callRules, isInitialized } from './wasm-loader.js'
 
 /**
  * Lint a GROQ query using the WASM-based linter
@@ -43,35 +51,135 @@ export function lint(query: string, config?: WasmLintConfig): Finding[] {
   }
 
   try {
-    // Call WASM function - returns JSON string
-    const resultJson = callLint(query)
-    const wasmFindings: WasmFinding[] = JSON.parse(resultJson)
-
-    // Filter by config if provided
-    let findings = wasmFindings
-    if (config?.rules) {
-      const rules = config.rules
-      findings = findings.filter((f) => {
-        const enabled = rules[f.ruleId]
-        return enabled !== false
-      })
-    }
+    // Call WASM function - returns plain JS objects with UTF-16 and
+    // line/column positions computed on the Rust side. Disabled rules are
+    // not run at all.
+    const findings = callLint(query, toWasmConfigJson(config))
 
     // Convert to our Finding type
-    return findings.map((wf) => convertFinding(wf, query))
+    return findings.map(convertFinding)
   } catch (error) {
-    if (error instanceof WasmError) {
-      throw error
-    }
+    throw toWasmError(error, 'Lint')
+  }
+}
 
-    // Parse errors from WASM come as thrown strings/errors
-    const message = error instanceof Error ? error.message : String(error)
+/**
+ * Lint many queries in one call
+ *
+ * The configuration is read once and the WASM boundary is crossed once per
+ * batch, which matters when linting thousands of extracted queries.
+ *
+ * @param queries - The queries to lint, each with an id to key its result by
+ * @param config - Optional configuration, as for `lint()`
+ * @returns For each id, its findings, or the error it failed with
+ * @throws {WasmError} If WASM is not initialized or the config is invalid
+ *
+ * @example
+ * ```typescript
+ * const results = lintBatch([
+ *   { id: 'posts.ts:3', query: '*[_type == "post"]' },
+ *   { id: 'authors.ts:7', query: '*[_type == "author"' },
+ * ])
+ * // { 'posts.ts:3': { findings: [] }, 'authors.ts:7': { error: WasmError } }
+ * ```
+ */
+export function lintBatch(
+  queries: WasmBatchQuery[],
+  config?: WasmLintConfig
+): Record<string, LintBatchResult> {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return convertBatchResults(queries, callLintBatch(queries, toWasmConfigJson(config)))
+  } catch (error) {
+    throw toWasmError(error, 'Lint')
+  }
+}
 
-    if (message.includes('parse') || message.includes('Parse')) {
-      throw new WasmError(`Failed to parse query: ${message}`, 'PARSE_ERROR')
+/**
+ * Convert the per-query results of a WASM batch, keyed by query id
+ */
+export function convertBatchResults(
+  queries: WasmBatchQuery[],
+  results: Record<string, WasmBatchResult>
+): Record<string, LintBatchResult> {
+  const converted: Record<string, LintBatchResult> = {}
+  for (const { id } of queries) {
+    const result = results[id]
+    if (!result) {
+      continue
+    }
+    if ('findings' in result) {
+      converted[id] = { findings: result.findings.map(convertFinding) }
+    } else {
+      const { span, ...details } = result.error
+      converted[id] = {
+        error: toWasmError({ ...details, start: span?.start, end: span?.end }, 'Lint'),
+      }
     }
+  }
+  return converted
+}
+
+/**
+ * Apply every machine-applicable fix to a GROQ query
+ *
+ * Lints and fixes repeatedly until nothing is left to fix. Suggestions that
+ * may change the query's results (`applicability: 'maybeIncorrect'`) are
+ * left for the user.
+ *
+ * @param query - The GROQ query string to fix
+ * @param config - Optional configuration, as for `lint()`; disabled rules
+ *   are not fixed
+ * @returns The rewritten query, the fixes applied and whether it converged
+ * @throws {WasmError} If WASM is not initialized or query parsing fails
+ *
+ * @example
+ * ```typescript
+ * fix('*[_type == "post"]{ "authorId": author->_id }').query
+ * // '*[_type == "post"]{ "authorId": author._ref }'
+ * ```
+ */
+export function fix(query: string, config?: WasmLintConfig): WasmFixResult {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  // Nothing to fix, and nothing for the parser to reject
+  if (!query.trim()) {
+    return { query, fixes: [], converged: true }
+  }
+
+  try {
+    return callFix(query, toWasmConfigJson(config))
+  } catch (error) {
+    throw toWasmError(error, 'Fix')
+  }
+}
+
+/**
+ * Every rule the WASM linter can report: groq-lint's own rules, then the
+ * schema, params and dataset-statistics rules
+ *
+ * @returns Rule metadata, in a stable order
+ * @throws {WasmError} If WASM is not initialized
+ *
+ * @example
+ * ```typescript
+ * const fixable = rules().filter((rule) => rule.fixable).map((rule) => rule.kebabId)
+ * ```
+ */
+export function rules(): WasmRule[] {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
 
-    throw new WasmError(`Lint failed: ${message}`, 'WASM_ERROR')
+  try {
+    return callRules()
+  } catch (error) {
+    throw toWasmError(error, 'Rules')
   }
 }
 
@@ -84,50 +192,50 @@ export async function lintAsync(query: string, config?: WasmLintConfig): Promise
   return lint(query, config)
 }
 
+/**
+ * Lint config as the JSON the Rust side reads, asking for positions
+ */
+export function toWasmConfigJson(config?: WasmLintConfig): string {
+  return JSON.stringify({
+    positions: true,
+    rules: config?.rules,
+    params: config?.params,
+    stats: config?.stats,
+  })
+}
+
 /**
  * Convert WASM finding to our Finding type
+ *
+ * Spans come from the positions computed in Rust, which `toWasmConfigJson`
+ * always asks for.
  */
-function convertFinding(wf: WasmFinding, query: string): Finding {
+export function convertFinding(wf: WasmFinding): Finding {
   return {
     ruleId: mapRuleId(wf.ruleId),
     message: wf.message,
     severity: mapSeverity(wf.severity),
-    span: byteSpanToSourceSpan(wf.start, wf.end, query),
+    ...(wf.range && { span: toSourceSpan(wf.range) }),
+    ...(wf.help && { help: wf.help }),
+    ...(wf.suggestions?.length && {
+      suggestions: wf.suggestions.map((s) => ({
+        description: s.description,
+        replacement: s.replacement,
+        ...(s.range && { span: toSourceSpan(s.range) }),
+        applicability: s.applicability,
+      })),
+    }),
+    fixable: wf.fixable,
   }
 }
 
-/**
- * Convert byte offsets to line/column source span
- */
-function byteSpanToSourceSpan(startByte: number, endByte: number, query: string): SourceSpan {
+function toSourceSpan(range: WasmRange): SourceSpan {
   return {
-    start: byteOffsetToLocation(startByte, query),
-    end: byteOffsetToLocation(endByte, query),
+    start: toSourceLocation(range.start),
+    end: toSourceLocation(range.end),
   }
 }
 
-/**
- * Convert byte offset to line/column location
- */
-function byteOffsetToLocation(offset: number, query: string): SourceLocation {
-  let line = 1
-  let column = 1
-  let currentOffset = 0
-
-  for (const char of query) {
-    if (currentOffset >= offset) {
-      break
-    }
-
-    if (char === '\n') {
-      line++
-      column = 1
-    } else {
-      column++
-    }
-
-    currentOffset++
-  }
-
-  return { line, column, offset }
+function toSourceLocation(position: WasmPosition): SourceLocation {
+  return { line: position.line, column: position.column, offset: position.utf16 }
 }
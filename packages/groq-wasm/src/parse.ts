@@ -0,0 +1,61 @@
+/**
+ * GROQ Parsing via WASM
+ *
+ * Exposes the syntax tree groq-parser builds, and what a query depends on,
+ * for tools that need more than a yes/no answer from `isValidSyntax()`.
+ */
+
+import { toWasmError, WasmError, type WasmAnalysis, type WasmSyntaxTree } from './types.js'
+import { callAnalyze, callParse, isInitialized } from './wasm-loader.js'
+
+/**
+ * Parse a GROQ query into groq-parser's syntax tree
+ *
+ * @param query - The GROQ query string to parse
+ * @returns `{ version, root }`; node spans are UTF-8 byte offsets
+ * @throws {WasmError} If WASM is not initialized or query parsing fails
+ *
+ * @example
+ * ```typescript
+ * const { root } = parse('*[_type == "post"]')
+ * root.type
+ * // 'Filter'
+ * ```
+ */
+export function parse(query: string): WasmSyntaxTree {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callParse(query)
+  } catch (error) {
+    throw toWasmError(error, 'Parse')
+  }
+}
+
+/**
+ * Report what a GROQ query depends on
+ *
+ * @param query - The GROQ query string to analyze
+ * @returns The document types it filters on, the fields it reads, the
+ *   references it follows, the parameters it uses and the functions it calls
+ * @throws {WasmError} If WASM is not initialized or query parsing fails
+ *
+ * @example
+ * ```typescript
+ * analyze('*[_type == "post" && slug.current == $slug]{ "author": author->name }')
+ * // { documentTypes: ['post'], parameters: [{ name: 'slug', ... }], ... }
+ * ```
+ */
+export function analyze(query: string): WasmAnalysis {
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callAnalyze(query)
+  } catch (error) {
+    throw toWasmError(error, 'Analyze')
+  }
+}
@@ -0,0 +1,291 @@
+/**
+ * Reusable schema handle via WASM
+ *
+ * Wraps the Rust `Schema` class, which parses and indexes `schema.json`
+ * once. Keep one alive and pass it to every call instead of the schema
+ * itself, so large schemas aren't serialized and re-read per query.
+ */
+
+import type { Finding } from '@sanity-labs/lint-core'
+import { convertBatchResults, convertFinding, toWasmConfigJson } from './lint.js'
+import {
+  type LintBatchResult,
+  toWasmError,
+  type WasmBatchQuery,
+  WasmError,
+  type WasmLintConfig,
+  type WasmValidator,
+  type WasmValidatorOptions,
+} from './types.js'
+import {
+  callLintWithSchema,
+  callTypegen,
+  callValidator,
+  createSchemaHandle,
+  isInitialized,
+  type WasmSchemaHandle,
+} from './wasm-loader.js'
+
+/**
+ * A parsed and indexed `schema.json`
+ *
+ * @example
+ * ```typescript
+ * import { initWasm, Schema } from '@sanity-labs/groq-wasm'
+ *
+ * await initWasm()
+ *
+ * const schema = new Schema(schemaJson)
+ * schema.lint('*[_type == "pst"]')
+ * // [{ ruleId: 'invalid-type-filter', ... }]
+ *
+ * // When schema.json changes on disk
+ * schema.update(newSchemaJson)
+ * ```
+ */
+export class Schema {
+  readonly #handle: WasmSchemaHandle
+
+  /**
+   * @param schema - The schema from `sanity schema extract`, parsed or as
+   *   JSON text
+   * @throws {WasmError} If WASM is not initialized or the schema is invalid
+   */
+  constructor(schema: unknown) {
+    if (!isInitialized()) {
+      throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+    }
+
+    try {
+      this.#handle = createSchemaHandle(toJson(schema))
+    } catch (error) {
+      throw toWasmError(error, 'Schema')
+    }
+  }
+
+  /**
+   * Replace the schema. On an invalid schema the previous one is kept.
+   *
+   * @returns false if the schema is unchanged and was not re-indexed
+   */
+  update(schema: unknown): boolean {
+    try {
+      return this.#handle.update(toJson(schema))
+    } catch (error) {
+      throw toWasmError(error, 'Schema')
+    }
+  }
+
+  /** Names of all document types, sorted */
+  documentTypes(): string[] {
+    return this.#handle.document_types()
+  }
+
+  /** Field names of a document type (empty for unknown types) */
+  fieldsForType(typeName: string): string[] {
+    return this.#handle.fields_for_type(typeName)
+  }
+
+  /** Document types a reference field can point at */
+  referenceTargets(typeName: string, field: string): string[] {
+    return this.#handle.reference_targets(typeName, field)
+  }
+
+  /**
+   * Lint a query, including the schema-aware rules
+   *
+   * @param query - The GROQ query string to lint
+   * @param config - Optional configuration, as for `lint()`
+   */
+  lint(query: string, config?: WasmLintConfig): Finding[] {
+    if (!query.trim()) {
+      return []
+    }
+
+    try {
+      return this.#handle.lint(query, toWasmConfigJson(config)).map(convertFinding)
+    } catch (error) {
+      throw toWasmError(error, 'Lint')
+    }
+  }
+
+  /**
+   * Lint many queries against this schema, as `lintBatch()` does
+   *
+   * @param queries - The queries to lint, each with an id to key its result by
+   * @param config - Optional configuration, as for `lint()`
+   */
+  lintBatch(queries: WasmBatchQuery[], config?: WasmLintConfig): Record<string, LintBatchResult> {
+    try {
+      return convertBatchResults(
+        queries,
+        this.#handle.lint_batch(queries, toWasmConfigJson(config))
+      )
+    } catch (error) {
+      throw toWasmError(error, 'Lint')
+    }
+  }
+
+  /**
+   * Generate TypeScript declarations for a query's result, as `typegen()`
+   * does
+   *
+   * @param query - The GROQ query string
+   * @param typeName - Name of the result type (default: `QueryResult`)
+   */
+  typegen(query: string, typeName?: string): string {
+    try {
+      return this.#handle.typegen(query, typeName ?? null)
+    } catch (error) {
+      throw toWasmError(error, 'Typegen')
+    }
+  }
+
+  /**
+   * Build runtime validators for a query's result, as `validator()` does
+   *
+   * @param query - The GROQ query string
+   * @param options - Name of the result type, and whether to emit Zod
+   */
+  validator(query: string, options?: WasmValidatorOptions): WasmValidator {
+    try {
+      return this.#handle.validator(query, JSON.stringify(options ?? {}))
+    } catch (error) {
+      throw toWasmError(error, 'Validator')
+    }
+  }
+
+  /** Release the WASM memory held by the schema */
+  free(): void {
+    this.#handle.free()
+  }
+}
+
+/**
+ * Lint a GROQ query, including the schema-aware rules (`invalid-type-filter`
+ * and `unknown-field`)
+ *
+ * Pass a `Schema` when linting more than one query against the same
+ * schema; anything else is parsed and indexed on every call.
+ *
+ * @param query - The GROQ query string to lint
+ * @param schema - A `Schema`, or the schema from `sanity schema extract`
+ *   (parsed or as JSON text)
+ * @param config - Optional configuration, as for `lint()`
+ * @returns Array of findings
+ * @throws {WasmError} If WASM is not initialized, the query doesn't parse or
+ *   the schema is invalid
+ *
+ * @example
+ * ```typescript
+ * lintWithSchema('*[_type == "pst"]', schemaJson)
+ * // [{ ruleId: 'invalid-type-filter', help: 'Did you mean: "post"?', ... }]
+ * ```
+ */
+export function lintWithSchema(query: string, schema: unknown, config?: WasmLintConfig): Finding[] {
+  if (schema instanceof Schema) {
+    return schema.lint(query, config)
+  }
+
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  if (!query.trim()) {
+    return []
+  }
+
+  try {
+    return callLintWithSchema(query, toJson(schema), toWasmConfigJson(config)).map(convertFinding)
+  } catch (error) {
+    throw toWasmError(error, 'Lint')
+  }
+}
+
+/**
+ * Generate TypeScript declarations for the result of a GROQ query
+ *
+ * The result type is inferred from the schema: projections become object
+ * types, `[0]` a single document or `null`, `->` the referenced document
+ * types. Values that can't be inferred are `unknown`. A projected key that
+ * can be `null` is optional instead, since null attributes are left out.
+ *
+ * @param query - The GROQ query string
+ * @param schema - A `Schema`, or the schema from `sanity schema extract`
+ *   (parsed or as JSON text)
+ * @param typeName - Name of the result type (default: `QueryResult`)
+ * @returns `export type` and `export interface` declarations
+ * @throws {WasmError} If WASM is not initialized, the query doesn't parse,
+ *   the schema is invalid or the type name is not an identifier
+ *
+ * @example
+ * ```typescript
+ * typegen('*[_type == "post"][0]{ title }', schemaJson, 'PostTitle')
+ * // export type PostTitle = {
+ * //   title: string;
+ * // } | null;
+ * ```
+ */
+export function typegen(query: string, schema: unknown, typeName?: string): string {
+  if (schema instanceof Schema) {
+    return schema.typegen(query, typeName)
+  }
+
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callTypegen(query, toJson(schema), typeName)
+  } catch (error) {
+    throw toWasmError(error, 'Typegen')
+  }
+}
+
+/**
+ * Build runtime validators for the result of a GROQ query
+ *
+ * The result type is inferred as for `typegen()`, and validators accept
+ * the same values its types do.
+ *
+ * @param query - The GROQ query string
+ * @param schema - A `Schema`, or the schema from `sanity schema extract`
+ *   (parsed or as JSON text)
+ * @param options - Name of the result type, and whether to emit Zod
+ * @returns A JSON Schema, and a Zod module with `zod: true`
+ * @throws {WasmError} If WASM is not initialized, the query doesn't parse,
+ *   the schema is invalid or the options are
+ *
+ * @example
+ * ```typescript
+ * const { zod } = validator('*[_type == "post"]{ title }', schemaJson, {
+ *   name: 'Posts',
+ *   zod: true,
+ * })
+ * // import { z } from "zod";
+ * // export const PostsSchema = z.array(...)
+ * ```
+ */
+export function validator(
+  query: string,
+  schema: unknown,
+  options?: WasmValidatorOptions
+): WasmValidator {
+  if (schema instanceof Schema) {
+    return schema.validator(query, options)
+  }
+
+  if (!isInitialized()) {
+    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
+  }
+
+  try {
+    return callValidator(query, toJson(schema), JSON.stringify(options ?? {}))
+  } catch (error) {
+    throw toWasmError(error, 'Validator')
+  }
+}
+
+function toJson(schema: unknown): string {
+  return typeof schema === 'string' ? schema : JSON.stringify(schema)
+}
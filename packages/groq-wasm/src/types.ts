@@ -1,15 +1,44 @@
 /**
  * Types for @sanity-labs/groq-wasm
  *
- * These types match the Rust API from groq-lint and groq-format,
- * converted to TypeScript conventions (snake_case → camelCase).
+ * The shapes of values returned by the WASM exports are declared once, on
+ * the Rust side (`TS_TYPES` in `rust/src/lib.rs`), and re-exported from the
+ * generated `wasm/groq_wasm.d.ts`. The options the TypeScript API takes, and
+ * its errors, are declared here.
  */
 
-/**
- * Severity levels matching Rust groq-lint
- * Rust: High, Medium, Low → TS: error, warning, info
- */
-export type WasmSeverity = 'high' | 'medium' | 'low'
+import type { Finding } from '@sanity-labs/lint-core'
+import type { WasmDatasetStats, WasmSeverity } from '../wasm/groq_wasm.js'
+
+export type {
+  WasmAnalysis,
+  WasmAppliedFix,
+  WasmBatchQuery,
+  WasmBatchResult,
+  WasmDatasetStats,
+  WasmDistribution,
+  WasmFieldRead,
+  WasmFieldStats,
+  WasmFinding,
+  WasmFixResult,
+  WasmFormatCheck,
+  WasmFormatEdits,
+  WasmGroqError,
+  WasmNode,
+  WasmOccurrence,
+  WasmPosition,
+  WasmRange,
+  WasmRule,
+  WasmRuleOption,
+  WasmSeverity,
+  WasmSuggestion,
+  WasmSyntaxTree,
+  WasmTestDifference,
+  WasmTestReport,
+  WasmTestResult,
+  WasmTextEdit,
+  WasmValidator,
+} from '../wasm/groq_wasm.js'
 
 /**
  * Map WASM severity to our standard severity
@@ -26,27 +55,59 @@ export function mapSeverity(wasmSeverity: WasmSeverity): 'error' | 'warning' | '
 }
 
 /**
- * A finding from the Rust linter (JSON format from WASM)
+ * Outcome for one query of a `lintBatch()` call: a query that fails to
+ * parse doesn't fail the batch
  */
-export interface WasmFinding {
-  /** Rule ID (snake_case from Rust) */
-  ruleId: string
-  /** Human-readable message */
-  message: string
-  /** Severity level */
-  severity: WasmSeverity
-  /** Start byte offset (0-based) */
-  start: number
-  /** End byte offset (0-based) */
-  end: number
+export type LintBatchResult = { findings: Finding[] } | { error: WasmError }
+
+/**
+ * Options for `validator()`
+ */
+export interface WasmValidatorOptions {
+  /** Name of the result type (default: `QueryResult`) */
+  name?: string
+  /** Also emit a Zod schema */
+  zod?: boolean
+}
+
+/**
+ * Options for a single rule
+ */
+export interface WasmRuleSetting {
+  /** Whether the rule runs (default: true) */
+  enabled?: boolean
+  /** Severity to report instead of the rule's default */
+  severity?: WasmSeverity | 'error' | 'warning' | 'info'
+  /**
+   * Rule-specific limit. Taken by `deep-pagination` (smallest offset
+   * reported), `many-joins` (most joins allowed) and the stats rules
+   * (`large-order`, `large-array-deref`); rejected for any other rule.
+   */
+  threshold?: number
 }
 
 /**
  * Configuration for linting
  */
 export interface WasmLintConfig {
-  /** Rules to enable/disable (rule_id → enabled) */
-  rules?: Record<string, boolean>
+  /**
+   * Rules to configure, by id in either the `snake_case` or `kebab-case`
+   * form. `false` disables a rule; an object overrides its options.
+   */
+  rules?: Record<string, boolean | WasmRuleSetting>
+  /**
+   * Params the query will be sent with. Enables the `missing-param`,
+   * `unused-param`, `invalid-param-type` and `deep-pagination-param-value`
+   * checks. The last replaces `deep-pagination-param`, and uses the
+   * `deep-pagination` threshold.
+   */
+  params?: Record<string, unknown>
+  /**
+   * Statistics of the dataset the query runs against, from `datasetStats()`.
+   * Enables the `unset-field-filter`, `large-order` and `large-array-deref`
+   * checks.
+   */
+  stats?: WasmDatasetStats
 }
 
 /**
@@ -57,13 +118,53 @@ export interface WasmFormatConfig {
   width?: number
 }
 
+/**
+ * Options for `fingerprint()`
+ */
+export interface WasmFingerprintOptions {
+  /** Treat the operands of `&&` and `||` as unordered */
+  ignoreFilterOrder?: boolean
+  /** Ignore the values of string, number, boolean and null literals */
+  literalPlaceholders?: boolean
+}
+
+/**
+ * Error codes for WASM operations
+ *
+ * `PARSE_ERROR`, `INVALID_CONFIG`, `INVALID_SCHEMA`, `INVALID_RANGE`,
+ * `INVALID_DATASET` and `EVALUATION_ERROR` come from the Rust `GroqError`;
+ * the others are raised on the TypeScript side.
+ */
+export type WasmErrorCode =
+  | 'NOT_INITIALIZED'
+  | 'PARSE_ERROR'
+  | 'INVALID_CONFIG'
+  | 'INVALID_SCHEMA'
+  | 'INVALID_RANGE'
+  | 'INVALID_DATASET'
+  | 'EVALUATION_ERROR'
+  | 'WASM_ERROR'
+
+/**
+ * Structured error thrown by the Rust side (`GroqError`)
+ */
+export interface WasmErrorDetails {
+  code: string
+  message: string
+  /** Start byte offset of the offending token, if known */
+  start?: number
+  /** End byte offset of the offending token, if known */
+  end?: number
+}
+
 /**
  * Error from WASM operations
  */
 export class WasmError extends Error {
   constructor(
     message: string,
-    public readonly code: 'NOT_INITIALIZED' | 'PARSE_ERROR' | 'WASM_ERROR'
+    public readonly code: WasmErrorCode,
+    public readonly span?: { start: number; end: number }
   ) {
     super(message)
     this.name = 'WasmError'
@@ -71,24 +172,57 @@ export class WasmError extends Error {
 }
 
 /**
- * Rule ID mapping from Rust (snake_case) to TS (kebab-case)
+ * Convert an error thrown by a WASM export into a WasmError
+ *
+ * @param error - The thrown value
+ * @param operation - Name used in the message of unexpected failures
  */
-export const RULE_ID_MAP: Record<string, string> = {
-  join_in_filter: 'join-in-filter',
-  join_to_get_id: 'join-to-get-id',
-  computed_value_in_filter: 'computed-value-in-filter',
-  match_on_id: 'match-on-id',
-  order_on_expr: 'order-on-expr',
-  deep_pagination: 'deep-pagination',
-  large_pages: 'large-pages',
-  non_literal_comparison: 'non-literal-comparison',
-  repeated_dereference: 'repeated-dereference',
-  count_in_correlated_subquery: 'count-in-correlated-subquery',
-  very_large_query: 'very-large-query',
-  extremely_large_query: 'extremely-large-query',
-  many_joins: 'many-joins',
+export function toWasmError(error: unknown, operation: string): WasmError {
+  if (error instanceof WasmError) {
+    return error
+  }
+
+  if (isWasmErrorDetails(error)) {
+    const span =
+      typeof error.start === 'number' && typeof error.end === 'number'
+        ? { start: error.start, end: error.end }
+        : undefined
+
+    switch (error.code) {
+      case 'PARSE_ERROR':
+        return new WasmError(`Failed to parse query: ${error.message}`, 'PARSE_ERROR', span)
+      case 'INVALID_CONFIG':
+      case 'INVALID_SCHEMA':
+      case 'INVALID_RANGE':
+      case 'INVALID_DATASET':
+        return new WasmError(error.message, error.code)
+      case 'EVALUATION_ERROR':
+        return new WasmError(error.message, error.code, span)
+      default:
+        return new WasmError(`${operation} failed: ${error.message}`, 'WASM_ERROR')
+    }
+  }
+
+  const message = error instanceof Error ? error.message : String(error)
+  return new WasmError(`${operation} failed: ${message}`, 'WASM_ERROR')
 }
 
+function isWasmErrorDetails(error: unknown): error is WasmErrorDetails {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as { code?: unknown }).code === 'string' &&
+    typeof (error as { message?: unknown }).message === 'string'
+  )
+}
+
+/**
+ * Rule ID mapping from Rust (snake_case) to TS (kebab-case)
+ *
+ * Filled from `rules()` by `initWasm()`.
+ */
+export const RULE_ID_MAP: Record<string, string> = {}
+
 /**
  * Convert Rust rule ID to TS convention
  */
@@ -5,15 +5,71 @@
  * Uses the `--target web` output from wasm-pack, which works in both Node.js and browsers.
  */
 
-import { WasmError } from './types.js'
+import {
+  RULE_ID_MAP,
+  WasmError,
+  type WasmAnalysis,
+  type WasmBatchQuery,
+  type WasmBatchResult,
+  type WasmDatasetStats,
+  type WasmFinding,
+  type WasmFixResult,
+  type WasmFormatCheck,
+  type WasmFormatEdits,
+  type WasmRule,
+  type WasmSyntaxTree,
+  type WasmTestReport,
+  type WasmTextEdit,
+  type WasmValidator,
+} from './types.js'
+import type { Dataset, Schema } from '../wasm/groq_wasm.js'
+
+/**
+ * The Rust `Schema` and `Dataset` classes, as generated by wasm-bindgen
+ */
+export type WasmSchemaHandle = Schema
+export type WasmDatasetHandle = Dataset
 
 // WASM module state
 let initialized = false
 let initPromise: Promise<void> | null = null
 
 // WASM module functions (set after initialization)
-let wasmLint: ((query: string) => string) | null = null
+let wasmLint: ((query: string, config?: string | null) => WasmFinding[]) | null = null
 let wasmFormat: ((query: string, width?: number | null) => string) | null = null
+let wasmMinify: ((query: string) => string) | null = null
+let wasmFingerprint: ((query: string, options?: string | null) => string) | null = null
+let wasmValidate: ((query: string) => void) | null = null
+let wasmRules: (() => WasmRule[]) | null = null
+let wasmLintBatch:
+  | ((queries: WasmBatchQuery[], config?: string | null) => Record<string, WasmBatchResult>)
+  | null = null
+let wasmFix: ((query: string, config?: string | null) => WasmFixResult) | null = null
+let wasmParse: ((query: string) => WasmSyntaxTree) | null = null
+let wasmAnalyze: ((query: string) => WasmAnalysis) | null = null
+let wasmLintWithSchema:
+  | ((query: string, schemaJson: string, config?: string | null) => WasmFinding[])
+  | null = null
+let wasmTypegen:
+  | ((query: string, schemaJson: string, typeName?: string | null) => string)
+  | null = null
+let wasmValidator:
+  | ((query: string, schemaJson: string, options?: string | null) => WasmValidator)
+  | null = null
+let wasmEvaluate:
+  | ((query: string, params: string | null | undefined, dataset: string) => unknown)
+  | null = null
+let wasmRunQueryTests: ((spec: string) => WasmTestReport) | null = null
+let wasmDatasetStats: ((dataset: string) => WasmDatasetStats) | null = null
+let WasmDataset: (new (ndjson: string) => WasmDatasetHandle) | null = null
+let WasmSchema: (new (schemaJson: string) => WasmSchemaHandle) | null = null
+let wasmCheckFormat: ((query: string, width?: number | null) => WasmFormatCheck) | null = null
+let wasmFormatEdits:
+  | ((query: string, width?: number | null, offsets?: Uint32Array | null) => WasmFormatEdits)
+  | null = null
+let wasmFormatRange:
+  | ((query: string, start: number, end: number, width?: number | null) => WasmTextEdit)
+  | null = null
 
 /**
  * Check if WASM modules are initialized
@@ -64,9 +120,34 @@ async function doInit(): Promise<void> {
       await wasmModule.default({ module_or_path: wasmUrl })
     }
 
-    // Store the functions
-    wasmLint = wasmModule.lint
-    wasmFormat = wasmModule.format
+    // Store the functions. Exports other than lint and format were added
+    // over time, so a binary built from an older crate may lack some: those
+    // stay null and throw when called instead of failing initialization.
+    wasmLint = wasmModule.lint ?? null
+    wasmFormat = wasmModule.format ?? null
+    wasmCheckFormat = wasmModule.check_format ?? null
+    wasmFormatRange = wasmModule.format_range ?? null
+    wasmFormatEdits = wasmModule.format_edits ?? null
+    wasmMinify = wasmModule.minify ?? null
+    wasmFingerprint = wasmModule.fingerprint ?? null
+    wasmValidate = wasmModule.validate ?? null
+    wasmRules = wasmModule.rules ?? null
+    wasmFix = wasmModule.fix ?? null
+    wasmLintBatch = wasmModule.lint_batch ?? null
+    wasmParse = wasmModule.parse ?? null
+    wasmAnalyze = wasmModule.analyze ?? null
+    wasmLintWithSchema = wasmModule.lint_with_schema ?? null
+    wasmTypegen = wasmModule.typegen ?? null
+    wasmValidator = wasmModule.validator ?? null
+    WasmSchema = wasmModule.Schema ?? null
+    wasmEvaluate = wasmModule.evaluate ?? null
+    WasmDataset = wasmModule.Dataset ?? null
+    wasmDatasetStats = wasmModule.dataset_stats ?? null
+    wasmRunQueryTests = wasmModule.run_query_tests ?? null
+
+    for (const rule of wasmRules?.() ?? []) {
+      RULE_ID_MAP[rule.id] = rule.kebabId
+    }
 
     initialized = true
   } catch (error) {
@@ -79,14 +160,59 @@ async function doInit(): Promise<void> {
 }
 
 /**
- * Call the WASM lint function
- * @throws {WasmError} If not initialized
+ * A WASM export, checking that WASM is initialized and the loaded build has it
+ * @throws {WasmError} If not initialized, or the build predates the export
  */
-export function callLint(query: string): string {
-  if (!initialized || !wasmLint) {
+function requireExport<T>(fn: T | null, name: string): T {
+  if (!initialized) {
     throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
   }
-  return wasmLint(query)
+  if (!fn) {
+    throw new WasmError(
+      `The loaded WASM build has no \`${name}\` export. Rebuild it with \`pnpm build:wasm\`.`,
+      'WASM_ERROR'
+    )
+  }
+  return fn
+}
+
+/**
+ * Call the WASM lint function
+ * @throws {WasmError} If not initialized
+ */
+export function callLint(query: string, config?: string): WasmFinding[] {
+  return requireExport(wasmLint, 'lint')(query, config ?? null)
+}
+
+/**
+ * Call the WASM lint_batch function
+ * @throws {WasmError} If not initialized
+ */
+export function callLintBatch(
+  queries: WasmBatchQuery[],
+  config?: string
+): Record<string, WasmBatchResult> {
+  return requireExport(wasmLintBatch, 'lint_batch')(queries, config ?? null)
+}
+
+/**
+ * Call the WASM fix function
+ * @throws {WasmError} If not initialized
+ */
+export function callFix(query: string, config?: string): WasmFixResult {
+  return requireExport(wasmFix, 'fix')(query, config ?? null)
+}
+
+/**
+ * Call the WASM lint_with_schema function
+ * @throws {WasmError} If not initialized
+ */
+export function callLintWithSchema(
+  query: string,
+  schemaJson: string,
+  config?: string
+): WasmFinding[] {
+  return requireExport(wasmLintWithSchema, 'lint_with_schema')(query, schemaJson, config ?? null)
 }
 
 /**
@@ -94,8 +220,148 @@ export function callLint(query: string): string {
  * @throws {WasmError} If not initialized
  */
 export function callFormat(query: string, width?: number): string {
-  if (!initialized || !wasmFormat) {
-    throw new WasmError('WASM not initialized. Call initWasm() first.', 'NOT_INITIALIZED')
-  }
-  return wasmFormat(query, width ?? null)
+  return requireExport(wasmFormat, 'format')(query, width ?? null)
+}
+
+/**
+ * Call the WASM check_format function
+ * @throws {WasmError} If not initialized
+ */
+export function callCheckFormat(query: string, width?: number): WasmFormatCheck {
+  return requireExport(wasmCheckFormat, 'check_format')(query, width ?? null)
+}
+
+/**
+ * Call the WASM format_range function
+ * @throws {WasmError} If not initialized
+ */
+export function callFormatRange(
+  query: string,
+  start: number,
+  end: number,
+  width?: number
+): WasmTextEdit {
+  return requireExport(wasmFormatRange, 'format_range')(query, start, end, width ?? null)
+}
+
+/**
+ * Call the WASM format_edits function
+ * @throws {WasmError} If not initialized
+ */
+export function callFormatEdits(
+  query: string,
+  width?: number,
+  offsets?: number[]
+): WasmFormatEdits {
+  return requireExport(wasmFormatEdits, 'format_edits')(
+    query,
+    width ?? null,
+    offsets ? Uint32Array.from(offsets) : null
+  )
+}
+
+/**
+ * Call the WASM minify function
+ * @throws {WasmError} If not initialized
+ */
+export function callMinify(query: string): string {
+  return requireExport(wasmMinify, 'minify')(query)
+}
+
+/**
+ * Call the WASM fingerprint function
+ * @throws {WasmError} If not initialized
+ */
+export function callFingerprint(query: string, options?: string): string {
+  return requireExport(wasmFingerprint, 'fingerprint')(query, options ?? null)
+}
+
+/**
+ * Call the WASM validate function
+ * @throws {WasmError} If not initialized
+ */
+export function callValidate(query: string): void {
+  requireExport(wasmValidate, 'validate')(query)
+}
+
+/**
+ * Call the WASM rules function
+ * @throws {WasmError} If not initialized
+ */
+export function callRules(): WasmRule[] {
+  return requireExport(wasmRules, 'rules')()
+}
+
+/**
+ * Call the WASM parse function
+ * @throws {WasmError} If not initialized
+ */
+export function callParse(query: string): WasmSyntaxTree {
+  return requireExport(wasmParse, 'parse')(query)
+}
+
+/**
+ * Call the WASM analyze function
+ * @throws {WasmError} If not initialized
+ */
+export function callAnalyze(query: string): WasmAnalysis {
+  return requireExport(wasmAnalyze, 'analyze')(query)
+}
+
+/**
+ * Call the WASM typegen function
+ * @throws {WasmError} If not initialized
+ */
+export function callTypegen(query: string, schemaJson: string, typeName?: string): string {
+  return requireExport(wasmTypegen, 'typegen')(query, schemaJson, typeName ?? null)
+}
+
+/**
+ * Call the WASM validator function
+ * @throws {WasmError} If not initialized
+ */
+export function callValidator(query: string, schemaJson: string, options?: string): WasmValidator {
+  return requireExport(wasmValidator, 'validator')(query, schemaJson, options ?? null)
+}
+
+/**
+ * Construct a WASM Schema handle
+ * @throws {WasmError} If not initialized
+ */
+export function createSchemaHandle(schemaJson: string): WasmSchemaHandle {
+  const Schema = requireExport(WasmSchema, 'Schema')
+  return new Schema(schemaJson)
+}
+
+/**
+ * Call the WASM evaluate function
+ * @throws {WasmError} If not initialized
+ */
+export function callEvaluate(query: string, params: string | undefined, dataset: string): unknown {
+  return requireExport(wasmEvaluate, 'evaluate')(query, params ?? null, dataset)
+}
+
+/**
+ * Call the WASM run_query_tests function
+ * @throws {WasmError} If not initialized
+ */
+export function callRunQueryTests(spec: string): WasmTestReport {
+  return requireExport(wasmRunQueryTests, 'run_query_tests')(spec)
+}
+
+/**
+ * Call the WASM dataset_stats function
+ * @throws {WasmError} If not initialized
+ */
+export function callDatasetStats(dataset: string): WasmDatasetStats {
+  return requireExport(wasmDatasetStats, 'dataset_stats')(dataset)
+}
+
+/**
+ * Construct a WASM Dataset handle
+ * @throws {WasmError} If not initialized
+ */
+export function createDatasetHandle(ndjson: string): WasmDatasetHandle {
+  const Dataset = requireExport(WasmDataset, 'Dataset')
+  return new Dataset(ndjson)
 }
@@ -1,64 +0,0 @@
-/* tslint:disable */
-/* eslint-disable */
-
-/**
- * Format a GROQ query.
- *
- * # Arguments
- * * `query` - The GROQ query string to format
- * * `width` - Maximum line width (default: 80)
- *
- * # Returns
- * The formatted query string
- */
-export function format(query: string, width?: number | null): string;
-
-export function init(): void;
-
-/**
- * Lint a GROQ query and return findings as JSON.
- *
- * # Arguments
- * * `query` - The GROQ query string to lint
- *
- * # Returns
- * A JSON string containing an array of findings
- */
-export function lint(query: string): string;
-
-export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
-
-export interface InitOutput {
-    readonly memory: WebAssembly.Memory;
-    readonly format: (a: number, b: number, c: number) => [number, number, number, number];
-    readonly init: () => void;
-    readonly lint: (a: number, b: number) => [number, number, number, number];
-    readonly __wbindgen_free: (a: number, b: number, c: number) => void;
-    readonly __wbindgen_malloc: (a: number, b: number) => number;
-    readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
-    readonly __wbindgen_externrefs: WebAssembly.Table;
-    readonly __externref_table_dealloc: (a: number) => void;
-    readonly __wbindgen_start: () => void;
-}
-
-export type SyncInitInput = BufferSource | WebAssembly.Module;
-
-/**
- * Instantiates the given `module`, which can either be bytes or
- * a precompiled `WebAssembly.Module`.
- *
- * @param {{ module: SyncInitInput }} module - Passing `SyncInitInput` directly is deprecated.
- *
- * @returns {InitOutput}
- */
-export function initSync(module: { module: SyncInitInput } | SyncInitInput): InitOutput;
-
-/**
- * If `module_or_path` is {RequestInfo} or {URL}, makes a request and
- * for everything else, calls `WebAssembly.instantiate` directly.
- *
- * @param {{ module_or_path: InitInput | Promise<InitInput> }} module_or_path - Passing `InitInput` directly is deprecated.
- *
- * @returns {Promise<InitOutput>}
- */
-export default function __wbg_init (module_or_path?: { module_or_path: InitInput | Promise<InitInput> } | InitInput | Promise<InitInput>): Promise<InitOutput>;